@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{GameAssets, GameState};
+
+use super::{
+    enemy::{Behaviour, EnemyKilledEvent},
+    explosion::ExplosionEvent,
+    health::{Health, MaxHealth},
+    pickup::SparkOrbBundle,
+    player::PlayerDamagedEvent,
+    projectile::Radius,
+    score::{Score, ScoreSource},
+    spark::SparkKind,
+    status::HitFlash,
+};
+
+/// Enemies won't roll affixes until the run's difficulty reaches this.
+const AFFIX_THRESHOLD: usize = 25;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Affix {
+    /// Moves half again as fast.
+    Fast,
+    /// Takes two extra hits to kill.
+    Armored,
+    /// Damages everything nearby when it dies.
+    Explosive,
+    /// Heals whenever it hurts the player.
+    Vampiric,
+}
+
+impl Affix {
+    const ALL: [Self; 4] = [Self::Fast, Self::Armored, Self::Explosive, Self::Vampiric];
+
+    fn color(self) -> Color {
+        match self {
+            Self::Fast => Color::rgb(1.0, 1.0, 0.3),
+            Self::Armored => Color::rgb(0.6, 0.6, 1.0),
+            Self::Explosive => Color::rgb(1.0, 0.4, 0.1),
+            Self::Vampiric => Color::rgb(0.8, 0.0, 0.3),
+        }
+    }
+}
+
+/// The modifiers an elite enemy spawned with.
+#[derive(Component, Deref, Clone)]
+pub struct Affixes(Vec<Affix>);
+
+impl Affixes {
+    /// Randomly picks affixes for a new enemy, getting more likely as the difficulty goes up.
+    pub fn roll(difficulty: usize) -> Option<Self> {
+        if difficulty < AFFIX_THRESHOLD {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+        let chance = (0.05 + 0.002 * (difficulty - AFFIX_THRESHOLD) as f64).min(0.4);
+        if !rng.gen_bool(chance) {
+            return None;
+        }
+
+        let count = if difficulty >= 2 * AFFIX_THRESHOLD && rng.gen_bool(0.3) {
+            2
+        } else {
+            1
+        };
+        let affixes = Affix::ALL.choose_multiple(&mut rng, count).copied();
+        Some(Self(affixes.collect()))
+    }
+
+    fn has(&self, affix: Affix) -> bool {
+        self.0.contains(&affix)
+    }
+}
+
+pub struct AffixPlugin;
+
+impl Plugin for AffixPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                apply_affixes,
+                handle_elite_deaths,
+                handle_vampiric_hits,
+                draw_affix_outlines,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn apply_affixes(
+    mut elite_query: Query<(&Affixes, &mut Behaviour, &mut Health, &mut MaxHealth), Added<Affixes>>,
+) {
+    for (affixes, mut behaviour, mut health, mut max_health) in &mut elite_query {
+        if affixes.has(Affix::Fast) {
+            behaviour.homing_force *= 1.5;
+        }
+        if affixes.has(Affix::Armored) {
+            **health += 2;
+            **max_health += 2;
+        }
+    }
+}
+
+/// Hands out bonus score and a rare spark for elites, and sets off explosive ones.
+fn handle_elite_deaths(
+    mut commands: Commands,
+    mut killed_events: EventReader<EnemyKilledEvent>,
+    mut explosion_writer: EventWriter<ExplosionEvent>,
+    mut score: ResMut<Score>,
+    assets: Res<GameAssets>,
+) {
+    for event in killed_events.read() {
+        let Some(affixes) = &event.affixes else {
+            continue;
+        };
+
+        score.add(ScoreSource::Elites, 5 * affixes.len());
+        let bonus = *SparkKind::RARE.choose(&mut rand::thread_rng()).unwrap();
+        commands.spawn(SparkOrbBundle::new(
+            bonus,
+            event.position - Vec3::X * 25.0,
+            &assets,
+        ));
+        if affixes.has(Affix::Explosive) {
+            explosion_writer.send(ExplosionEvent {
+                position: event.position,
+                radius: 90.0,
+                damage: 1,
+                team: None,
+            });
+        }
+    }
+}
+
+fn handle_vampiric_hits(
+    mut commands: Commands,
+    mut damaged_events: EventReader<PlayerDamagedEvent>,
+    mut elite_query: Query<(&Affixes, &mut Health, &MaxHealth)>,
+) {
+    for event in damaged_events.read() {
+        let Some(source) = event.source else {
+            continue;
+        };
+        let Ok((affixes, mut health, max_health)) = elite_query.get_mut(source) else {
+            continue;
+        };
+        if affixes.has(Affix::Vampiric) && **health < **max_health {
+            **health += 1;
+            commands.entity(source).try_insert(HitFlash::default());
+        }
+    }
+}
+
+fn draw_affix_outlines(elite_query: Query<(&Affixes, &Transform, &Radius)>, mut gizmos: Gizmos) {
+    for (affixes, transform, radius) in &elite_query {
+        for (i, affix) in affixes.iter().enumerate() {
+            gizmos.circle_2d(
+                transform.translation.truncate(),
+                radius.0 + 3.0 + 4.0 * i as f32,
+                affix.color(),
+            );
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{GameAssets, GameState};
+
+use super::{
+    enemy::{lead_target, Aim, AttackTimer, EnemyBundle, EnemyKind, Target},
+    health::{Health, MaxHealth},
+    pattern::BulletPattern,
+    projectile::{Motion, Team},
+    stats::RunStats,
+    status::{HitFlash, StatusEffect, StatusEffects},
+    Game,
+};
+
+/// Marks the current boss and tracks which phase of the fight it is in.
+#[derive(Component)]
+pub struct Boss {
+    phase: usize,
+    volleys: usize,
+}
+
+/// The difficulty at which the next boss shows up.
+#[derive(Resource)]
+struct NextBoss(usize);
+
+impl Default for NextBoss {
+    fn default() -> Self {
+        Self(40)
+    }
+}
+
+#[derive(Component)]
+struct BossHealthBar;
+
+#[derive(Component)]
+struct BossHealthFill;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NextBoss>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_next_boss, spawn_boss_health_bar),
+            )
+            .add_systems(
+                Update,
+                (spawn_boss, update_boss_phase, update_boss_health_bar)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_next_boss(mut next_boss: ResMut<NextBoss>) {
+    *next_boss = NextBoss::default();
+}
+
+fn spawn_boss(
+    mut commands: Commands,
+    mut next_boss: ResMut<NextBoss>,
+    stats: Res<RunStats>,
+    boss_query: Query<(), With<Boss>>,
+    assets: Res<GameAssets>,
+) {
+    if stats.difficulty() < next_boss.0 || !boss_query.is_empty() {
+        return;
+    }
+    next_boss.0 = stats.difficulty() + 80;
+
+    let bundle = EnemyBundle::new(EnemyKind::Boss, &assets);
+    commands.spawn(bundle).insert((
+        Transform::from_xyz(0.0, 600.0, 0.0),
+        Boss {
+            phase: 0,
+            volleys: 0,
+        },
+    ));
+}
+
+/// Moves on to the next phase whenever the boss loses another third of its health.
+fn update_boss_phase(
+    mut commands: Commands,
+    mut boss_query: Query<
+        (
+            Entity,
+            &mut Boss,
+            &Health,
+            &MaxHealth,
+            &mut AttackTimer,
+            &mut StatusEffects,
+        ),
+        Changed<Health>,
+    >,
+) {
+    for (entity, mut boss, health, max_health, mut attack_timer, mut effects) in &mut boss_query {
+        let phase = max_health.saturating_sub(**health) * 3 / **max_health;
+        if phase <= boss.phase {
+            continue;
+        }
+
+        boss.phase = phase;
+        let duration = attack_timer.duration().mul_f32(0.7);
+        attack_timer.set_duration(duration);
+        effects.apply(StatusEffect::stun(1.0));
+        commands.entity(entity).try_insert(HitFlash::default());
+    }
+}
+
+pub(super) fn handle_boss_attack(
+    In((boss_entity, target)): In<(Entity, Target)>,
+    mut commands: Commands,
+    mut boss_query: Query<(&Transform, &Team, &Aim, &mut Boss)>,
+) {
+    let Ok((boss_transform, &team, aim, mut boss)) = boss_query.get_mut(boss_entity) else {
+        return; // Boss died before command was executed
+    };
+    boss.volleys += 1;
+
+    let speed = 180.0;
+    let offset = (target.position - boss_transform.translation).truncate();
+    let aimed = Quat::from_rotation_arc_2d(
+        Vec2::Y,
+        lead_target(offset, target.velocity * aim.lead, speed).normalize_or_zero(),
+    );
+
+    let pattern = match boss.phase {
+        0 => BulletPattern::spread(5, TAU / 6.0, speed)
+            .speeds(0.8 * speed, 1.2 * speed)
+            .motion(Motion::default().waving(15.0, 2.0)),
+        1 => BulletPattern::ring(16, speed),
+        // Alternate curving rings, which are much harder to slip through, with a spinning stream
+        _ if boss.volleys % 2 == 0 => {
+            BulletPattern::ring(20, speed).motion(Motion::default().curving(0.6))
+        }
+        _ => BulletPattern::single(speed)
+            .bursts(24, 0.05)
+            .rotating(TAU / 12.0),
+    };
+    pattern
+        .bullet(25.0, Color::WHITE)
+        .emit(&mut commands, boss_entity, aimed, team);
+}
+
+fn spawn_boss_health_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(15.0),
+                    left: Val::Percent(30.0),
+                    width: Val::Percent(40.0),
+                    height: Val::Px(20.0),
+                    padding: UiRect::all(Val::Px(3.0)),
+                    display: Display::None,
+                    ..Default::default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..Default::default()
+            },
+            BossHealthBar,
+            Game,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..Default::default()
+                    },
+                    background_color: Color::rgb(0.8, 0.1, 0.1).into(),
+                    ..Default::default()
+                },
+                BossHealthFill,
+            ));
+        });
+}
+
+fn update_boss_health_bar(
+    boss_query: Query<(&Health, &MaxHealth), With<Boss>>,
+    mut bar_query: Query<&mut Style, (With<BossHealthBar>, Without<BossHealthFill>)>,
+    mut fill_query: Query<&mut Style, (With<BossHealthFill>, Without<BossHealthBar>)>,
+) {
+    let mut bar_style = bar_query.single_mut();
+    let Ok((health, max_health)) = boss_query.get_single() else {
+        bar_style.display = Display::None;
+        return;
+    };
+
+    bar_style.display = Display::Flex;
+    fill_query.single_mut().width = Val::Percent(100.0 * **health as f32 / **max_health as f32);
+}
@@ -1,34 +1,214 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
 use std::time::Duration;
 
-use bevy::ecs::system::{Command, RunSystemOnce};
+use bevy::audio::Volume;
+use bevy::ecs::query::Has;
+use bevy::ecs::system::{Command, EntityCommands, RunSystemOnce, SystemParam};
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::{GameAssets, GameState};
 
 use super::{
-    health::Health,
-    player::{HurtPlayerEvent, Player, Sparks},
-    projectile::{Projectile, ProjectileBundle, Radius, Team, Velocity},
-    Game,
+    affix::Affixes,
+    boss,
+    explosion::ExplosionEvent,
+    health::{Health, MaxHealth},
+    pattern::BulletPattern,
+    pickup::SparkOrbBundle,
+    player::{DamageCause, HurtPlayerEvent, Player, PlayerVelocity, Sparks},
+    projectile::{Motion, Radius, Shield, Team},
+    raycast::Colliders,
+    spark::SparkKind,
+    status::{self, HitFlash, StatusEffect, StatusEffects, Tint},
+    time_scale::TimeScale,
+    Game, ARENA_EXTENT,
 };
 
 #[derive(Component)]
 pub struct Enemy;
 
-#[derive(Component, Clone, Copy)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EnemyKind {
     Basic,
     Ranged,
+    Boss,
+    Splitter,
+    Charger,
+    Shielded,
+    Healer,
+    Thief,
+    Bomber,
+}
+
+impl EnemyKind {
+    pub const ALL: [Self; 9] = [
+        Self::Basic,
+        Self::Ranged,
+        Self::Boss,
+        Self::Splitter,
+        Self::Charger,
+        Self::Shielded,
+        Self::Healer,
+        Self::Thief,
+        Self::Bomber,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Basic => "Basic",
+            Self::Ranged => "Ranged",
+            Self::Boss => "Boss",
+            Self::Splitter => "Splitter",
+            Self::Charger => "Charger",
+            Self::Shielded => "Shielded",
+            Self::Healer => "Healer",
+            Self::Thief => "Thief",
+            Self::Bomber => "Bomber",
+        }
+    }
+
+    /// The spark it drops when killed.
+    pub fn spark(self) -> SparkKind {
+        match self {
+            Self::Basic => SparkKind::Basic,
+            Self::Ranged => SparkKind::Ranged,
+            Self::Boss => SparkKind::Boss,
+            Self::Splitter => SparkKind::Splitter,
+            Self::Charger => SparkKind::Charger,
+            Self::Shielded => SparkKind::Shielded,
+            Self::Healer => SparkKind::Healer,
+            Self::Thief => SparkKind::Thief,
+            Self::Bomber => SparkKind::Bomber,
+        }
+    }
+
+    /// Score for killing one, before the combo multiplier.
+    pub fn points(self) -> usize {
+        match self {
+            Self::Basic | Self::Splitter => 1,
+            Self::Ranged | Self::Charger | Self::Bomber => 2,
+            Self::Shielded | Self::Healer | Self::Thief => 3,
+            Self::Boss => 25,
+        }
+    }
+}
+
+/// How many times a splitter's ancestors have split.
+#[derive(Component, Clone, Copy)]
+struct Generation(u32);
+
+impl Generation {
+    const MAX: u32 = 2;
 }
 
 #[derive(Component)]
-struct Behaviour {
-    homing_force: f32,
+pub struct Behaviour {
+    pub homing_force: f32,
     separating_force: f32,
+    /// How far from its target to try and stay, 0 means as close as possible.
+    keep_away: f32,
+}
+
+impl Behaviour {
+    pub fn new(homing_force: f32, separating_force: f32, keep_away: f32) -> Self {
+        Self {
+            homing_force,
+            separating_force,
+            keep_away,
+        }
+    }
+}
+
+/// Steals the player's next spark and runs off with it.
+#[derive(Component, Default)]
+struct Thief {
+    stolen: Option<SparkKind>,
+}
+
+/// Periodically heals other enemies within `radius`.
+#[derive(Component)]
+struct Support {
+    radius: f32,
 }
 
 #[derive(Component, Deref, DerefMut)]
-struct AttackTimer(Timer);
+pub struct AttackTimer(Timer);
+
+/// How ranged enemies aim their shots.
+#[derive(Component)]
+pub struct Aim {
+    /// How far ahead of the player to aim, 0 aims at the player and 1 leads perfectly.
+    pub lead: f32,
+    /// Shots deviate by up to `(1 - accuracy)` of a quarter turn.
+    pub accuracy: f32,
+}
+
+impl Default for Aim {
+    /// Aims straight at the player.
+    fn default() -> Self {
+        Self {
+            lead: 0.0,
+            accuracy: 1.0,
+        }
+    }
+}
+
+/// Dashes in a straight line once its wind-up finishes, then recovers for a moment.
+#[derive(Component)]
+struct Charge {
+    speed: f32,
+    direction: Vec3,
+    timer: Timer,
+    /// Everything already hit during the current dash.
+    struck: Vec<Entity>,
+}
+
+impl Charge {
+    fn new(speed: f32, seconds: f32) -> Self {
+        let mut timer = Timer::from_seconds(seconds, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self {
+            speed,
+            direction: Vec3::ZERO,
+            timer,
+            struck: Vec::new(),
+        }
+    }
+
+    fn is_dashing(&self) -> bool {
+        !self.timer.finished()
+    }
+
+    fn stop(&mut self) {
+        let duration = self.timer.duration();
+        self.timer.tick(duration);
+    }
+}
+
+/// Telegraphs an attack before it lands, giving the player a chance to react.
+#[derive(Component)]
+struct WindUp {
+    timer: Timer,
+    /// The target needs to be this close before the enemy starts winding up.
+    range: f32,
+    sound: Option<Handle<AudioSource>>,
+    active: bool,
+    /// Where the attack is being aimed.
+    target: Vec3,
+}
+
+impl WindUp {
+    fn new(seconds: f32, range: f32, sound: Option<Handle<AudioSource>>) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            range,
+            sound,
+            active: false,
+            target: Vec3::ZERO,
+        }
+    }
+}
 
 #[derive(Bundle)]
 pub struct EnemyBundle {
@@ -39,91 +219,300 @@ pub struct EnemyBundle {
     sprite: SpriteBundle,
     team: Team,
     health: Health,
+    max_health: MaxHealth,
     radius: Radius,
     behaviour: Behaviour,
     attack_timer: AttackTimer,
+    wind_up: WindUp,
+    aim: Aim,
+    status_effects: StatusEffects,
+    tint: Tint,
 }
 
-impl EnemyBundle {
-    pub fn new(kind: EnemyKind, assets: &GameAssets) -> Self {
+/// The numbers that set each kind of enemy apart.
+struct EnemyStats {
+    texture: Handle<Image>,
+    size: f32,
+    health: usize,
+    behaviour: Behaviour,
+    /// Seconds between attacks once the wind-up finishes.
+    attack_interval: f32,
+    wind_up: WindUp,
+    aim: Aim,
+    tint: Color,
+}
+
+impl EnemyStats {
+    fn of(kind: EnemyKind, assets: &GameAssets) -> Self {
         match kind {
             EnemyKind::Basic => Self {
-                enemy: Enemy,
-                game: Game,
-                kind,
-                sprite: SpriteBundle {
-                    texture: assets.basic_enemy.clone(),
-                    sprite: Sprite {
-                        custom_size: Some(Vec2 { x: 39.0, y: 39.0 }),
-                        ..Default::default()
-                    },
-                    ..Default::default()
+                texture: assets.basic_enemy.clone(),
+                size: 39.0,
+                health: 1,
+                behaviour: Behaviour::new(75.0, 75.0, 0.0),
+                attack_interval: 0.0,
+                wind_up: WindUp::new(0.3, 60.0, None),
+                aim: Aim::default(),
+                tint: Color::WHITE,
+            },
+            EnemyKind::Ranged => Self {
+                texture: assets.ranged_enemy.clone(),
+                size: 47.0,
+                health: 2,
+                behaviour: Behaviour::new(60.0, 150.0, 0.0),
+                attack_interval: 1.0,
+                wind_up: WindUp::new(0.5, f32::INFINITY, Some(assets.shoot.clone())),
+                aim: Aim {
+                    lead: 0.6,
+                    accuracy: 0.9,
                 },
-                team: Team::Hostile,
-                health: Health(1),
-                radius: Radius(19.5),
-                behaviour: Behaviour {
-                    homing_force: 75.0,
-                    separating_force: 75.0,
+                tint: Color::WHITE,
+            },
+            EnemyKind::Boss => Self {
+                texture: assets.ranged_enemy.clone(),
+                size: 120.0,
+                health: 30,
+                behaviour: Behaviour::new(40.0, 300.0, 0.0),
+                attack_interval: 2.5,
+                wind_up: WindUp::new(0.8, f32::INFINITY, Some(assets.shoot.clone())),
+                aim: Aim {
+                    lead: 1.0,
+                    accuracy: 1.0,
                 },
-                attack_timer: AttackTimer(Timer::from_seconds(0.0, TimerMode::Repeating)),
+                tint: Color::WHITE,
             },
-            EnemyKind::Ranged => Self {
-                enemy: Enemy,
-                game: Game,
-                kind,
-                sprite: SpriteBundle {
-                    texture: assets.ranged_enemy.clone(),
-                    sprite: Sprite {
-                        custom_size: Some(Vec2 { x: 47.0, y: 47.0 }),
-                        ..Default::default()
-                    },
+            EnemyKind::Splitter => Self {
+                texture: assets.basic_enemy.clone(),
+                size: 55.0,
+                health: 3,
+                behaviour: Behaviour::new(55.0, 100.0, 0.0),
+                attack_interval: 0.0,
+                wind_up: WindUp::new(0.3, 70.0, None),
+                aim: Aim::default(),
+                tint: Color::rgb(0.6, 1.0, 0.6),
+            },
+            EnemyKind::Charger => Self {
+                texture: assets.basic_enemy.clone(),
+                size: 45.0,
+                health: 2,
+                behaviour: Behaviour::new(50.0, 100.0, 0.0),
+                attack_interval: 3.0,
+                wind_up: WindUp::new(0.7, 350.0, None),
+                aim: Aim::default(),
+                tint: Color::rgb(1.0, 0.7, 0.4),
+            },
+            EnemyKind::Shielded => Self {
+                texture: assets.ranged_enemy.clone(),
+                size: 50.0,
+                health: 3,
+                behaviour: Behaviour::new(45.0, 120.0, 0.0),
+                attack_interval: 0.0,
+                wind_up: WindUp::new(0.4, 75.0, None),
+                aim: Aim::default(),
+                tint: Color::rgb(0.7, 0.7, 0.9),
+            },
+            EnemyKind::Healer => Self {
+                texture: assets.ranged_enemy.clone(),
+                size: 42.0,
+                health: 2,
+                behaviour: Behaviour::new(70.0, 150.0, 250.0),
+                attack_interval: 2.5,
+                wind_up: WindUp::new(0.6, f32::INFINITY, None),
+                aim: Aim::default(),
+                tint: Color::rgb(1.0, 0.6, 0.8),
+            },
+            EnemyKind::Thief => Self {
+                texture: assets.basic_enemy.clone(),
+                size: 32.0,
+                health: 2,
+                behaviour: Behaviour::new(110.0, 60.0, 0.0),
+                attack_interval: 0.0,
+                wind_up: WindUp::new(0.15, 55.0, None),
+                aim: Aim::default(),
+                tint: Color::rgb(0.5, 0.5, 0.5),
+            },
+            EnemyKind::Bomber => Self {
+                texture: assets.basic_enemy.clone(),
+                size: 44.0,
+                health: 1,
+                behaviour: Behaviour::new(65.0, 90.0, 0.0),
+                attack_interval: 0.0,
+                wind_up: WindUp::new(0.3, 65.0, None),
+                aim: Aim::default(),
+                tint: Color::rgb(1.0, 0.5, 0.3),
+            },
+        }
+    }
+}
+
+impl EnemyBundle {
+    pub fn new(kind: EnemyKind, assets: &GameAssets) -> Self {
+        let stats = EnemyStats::of(kind, assets);
+        Self {
+            enemy: Enemy,
+            game: Game,
+            kind,
+            sprite: SpriteBundle {
+                texture: stats.texture,
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(stats.size)),
                     ..Default::default()
                 },
-                team: Team::Hostile,
-                health: Health(2),
-                radius: Radius(23.5),
-                behaviour: Behaviour {
-                    homing_force: 60.0,
-                    separating_force: 150.0,
-                },
-                attack_timer: AttackTimer(Timer::from_seconds(1.0, TimerMode::Repeating)),
+                ..Default::default()
             },
+            team: Team::Hostile,
+            health: Health(stats.health),
+            max_health: MaxHealth(stats.health),
+            radius: Radius(stats.size / 2.0),
+            behaviour: stats.behaviour,
+            attack_timer: AttackTimer(Timer::from_seconds(
+                stats.attack_interval,
+                TimerMode::Repeating,
+            )),
+            wind_up: stats.wind_up,
+            aim: stats.aim,
+            status_effects: StatusEffects::default(),
+            tint: Tint(stats.tint),
+        }
+    }
+
+    /// Spawns an enemy along with the components that drive its kind's special behaviour.
+    pub fn spawn<'w, 's, 'a>(
+        commands: &'a mut Commands<'w, 's>,
+        kind: EnemyKind,
+        assets: &GameAssets,
+    ) -> EntityCommands<'w, 's, 'a> {
+        let mut enemy = commands.spawn(Self::new(kind, assets));
+        match kind {
+            EnemyKind::Basic | EnemyKind::Ranged | EnemyKind::Boss | EnemyKind::Bomber => {}
+            EnemyKind::Splitter => {
+                enemy.insert(Generation(0));
+            }
+            EnemyKind::Charger => {
+                enemy.insert(Charge::new(700.0, 0.6));
+            }
+            EnemyKind::Shielded => {
+                enemy.insert(Shield {
+                    arc: 1.0,
+                    reflect: true,
+                });
+            }
+            EnemyKind::Healer => {
+                enemy.insert(Support { radius: 200.0 });
+            }
+            EnemyKind::Thief => {
+                enemy.insert(Thief::default());
+            }
         }
+        enemy
+    }
+
+    /// A splitter that gets smaller and faster with every generation.
+    fn splitter(generation: u32, assets: &GameAssets) -> (Self, Generation) {
+        let mut bundle = Self::new(EnemyKind::Splitter, assets);
+        let scale = 0.7_f32.powi(generation as i32);
+        bundle.sprite.sprite.custom_size = Some(Vec2::splat(55.0 * scale));
+        bundle.radius = Radius(27.5 * scale);
+        bundle.health = Health(3 - generation as usize);
+        bundle.max_health = MaxHealth(3 - generation as usize);
+        bundle.behaviour.homing_force *= 1.4_f32.powi(generation as i32);
+        (bundle, Generation(generation))
     }
 }
 
 #[derive(Component)]
 struct Puff(Timer);
 
+/// Health as of the last time an enemy was checked, for working out how much damage it took.
+#[derive(Component)]
+struct LastHealth(usize);
+
+/// Sent whenever an enemy loses health.
+#[derive(Event)]
+pub struct EnemyHitEvent {
+    pub damage: usize,
+}
+
+/// Sent once for every enemy that dies.
+#[derive(Event)]
+pub struct EnemyKilledEvent {
+    pub kind: EnemyKind,
+    pub position: Vec3,
+    /// What the enemy had if it was an elite.
+    pub affixes: Option<Affixes>,
+}
+
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                handle_health,
-                fetch_positions.pipe(movement),
-                handle_attacks,
-                despawn_puffs,
-            )
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.add_event::<EnemyHitEvent>()
+            .add_event::<EnemyKilledEvent>()
+            .add_systems(
+                Update,
+                (
+                    report_hits,
+                    handle_health,
+                    fetch_positions.pipe(movement),
+                    handle_charges,
+                    handle_charge_hits,
+                    handle_attacks,
+                    apply_charms,
+                    draw_tethers,
+                    handle_escapes,
+                    telegraph_attacks.after(status::tint_status_effects),
+                    despawn_puffs,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn report_hits(
+    mut commands: Commands,
+    mut enemy_query: Query<
+        (Entity, &Health, Option<&mut LastHealth>),
+        (Changed<Health>, With<Enemy>),
+    >,
+    mut hit_writer: EventWriter<EnemyHitEvent>,
+) {
+    for (entity, health, last_health) in &mut enemy_query {
+        let Some(mut last_health) = last_health else {
+            // Just spawned, there's nothing to compare against yet
+            commands.entity(entity).insert(LastHealth(**health));
+            continue;
+        };
+        if **health < last_health.0 {
+            hit_writer.send(EnemyHitEvent {
+                damage: last_health.0 - **health,
+            });
+        }
+        last_health.0 = **health;
     }
 }
 
 fn handle_health(
     mut commands: Commands,
-    enemy_query: Query<(Entity, &Health, &EnemyKind, &Transform), Changed<Health>>,
-    mut player_query: Query<&mut Sparks>,
+    enemy_query: Query<
+        (Entity, &Health, &EnemyKind, &Transform, Option<&Affixes>),
+        Changed<Health>,
+    >,
+    mut killed_writer: EventWriter<EnemyKilledEvent>,
     assets: Res<GameAssets>,
 ) {
-    let mut sparks = player_query.single_mut();
-    for (entity, health, kind, transform) in &enemy_query {
+    for (entity, health, kind, transform, affixes) in &enemy_query {
         if **health != 0 {
             continue;
         }
+        killed_writer.send(EnemyKilledEvent {
+            kind: *kind,
+            position: transform.translation,
+            affixes: affixes.cloned(),
+        });
+        commands.add(DeathCommand {
+            enemy: entity,
+            kind: *kind,
+        });
         commands.entity(entity).despawn_recursive();
         commands.spawn((
             SpriteBundle {
@@ -138,55 +527,408 @@ fn handle_health(
             Puff(Timer::new(Duration::from_millis(500), TimerMode::Once)),
             Game,
         ));
-        sparks.push_back(*kind);
+        commands.spawn(SparkOrbBundle::new(
+            kind.spark(),
+            transform.translation,
+            &assets,
+        ));
     }
 }
 
-fn fetch_positions(enemy_query: Query<&Transform, With<Enemy>>) -> Vec<Vec3> {
-    enemy_query
+/// Runs an enemy's on-death behaviour before it is despawned.
+struct DeathCommand {
+    enemy: Entity,
+    kind: EnemyKind,
+}
+
+impl Command for DeathCommand {
+    fn apply(self, world: &mut World) {
+        match self.kind {
+            EnemyKind::Splitter => world.run_system_once_with(self.enemy, handle_splitter_death),
+            EnemyKind::Thief => world.run_system_once_with(self.enemy, handle_thief_death),
+            EnemyKind::Bomber => world.run_system_once_with(self.enemy, handle_bomber_death),
+            EnemyKind::Basic
+            | EnemyKind::Ranged
+            | EnemyKind::Boss
+            | EnemyKind::Charger
+            | EnemyKind::Shielded
+            | EnemyKind::Healer => {}
+        }
+    }
+}
+
+fn handle_splitter_death(
+    In(enemy): In<Entity>,
+    mut commands: Commands,
+    enemy_query: Query<(&Transform, &Generation)>,
+    assets: Res<GameAssets>,
+) {
+    let Ok((transform, &Generation(generation))) = enemy_query.get(enemy) else {
+        return;
+    };
+    if generation >= Generation::MAX {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let count = rng.gen_range(2..=3);
+    let offset = rng.gen_range(0.0..TAU);
+    for i in 0..count {
+        let angle = offset + TAU * i as f32 / count as f32;
+        let position = transform.translation + Quat::from_rotation_z(angle) * Vec3::Y * 20.0;
+        commands
+            .spawn(EnemyBundle::splitter(generation + 1, &assets))
+            .insert(Transform::from_translation(position));
+    }
+}
+
+/// Gives back whatever the thief was carrying, with an extra copy as a reward for catching it.
+fn handle_thief_death(
+    In(enemy): In<Entity>,
+    mut commands: Commands,
+    thief_query: Query<(&Transform, &Thief)>,
+    assets: Res<GameAssets>,
+) {
+    let Ok((
+        transform,
+        Thief {
+            stolen: Some(stolen),
+        },
+    )) = thief_query.get(enemy)
+    else {
+        return;
+    };
+    // Scatter them a little so they don't land on top of the thief's own spark
+    for offset in [Vec3::X, Vec3::NEG_X] {
+        commands.spawn(SparkOrbBundle::new(
+            *stolen,
+            transform.translation + offset * 25.0,
+            &assets,
+        ));
+    }
+}
+
+fn handle_bomber_death(
+    In(enemy): In<Entity>,
+    enemy_query: Query<&Transform>,
+    mut explosion_writer: EventWriter<ExplosionEvent>,
+) {
+    let Ok(transform) = enemy_query.get(enemy) else {
+        return;
+    };
+    explosion_writer.send(ExplosionEvent {
+        position: transform.translation,
+        radius: 100.0,
+        damage: 2,
+        team: None,
+    });
+}
+
+/// Something steering bodies can home in on, or need to keep clear of.
+struct Body {
+    position: Vec3,
+    team: Team,
+    steering: bool,
+}
+
+fn fetch_positions(
+    body_query: Query<(&Transform, &Team, Has<Behaviour>), With<Health>>,
+) -> Vec<Body> {
+    body_query
         .iter()
-        .map(|transform| transform.translation)
+        .map(|(transform, &team, steering)| Body {
+            position: transform.translation,
+            team,
+            steering,
+        })
         .collect()
 }
 
+/// The closest body that isn't on `team`.
+fn nearest_opponent(bodies: &[Body], position: Vec3, team: Team) -> Option<Vec3> {
+    bodies
+        .iter()
+        .filter(|body| body.team != team)
+        .map(|body| body.position)
+        .min_by(|a, b| {
+            a.distance_squared(position)
+                .total_cmp(&b.distance_squared(position))
+        })
+}
+
 fn movement(
-    In(other_positions): In<Vec<Vec3>>,
-    mut enemy_query: Query<(&mut Transform, &Behaviour), Without<Player>>,
-    player_query: Query<&Transform, With<Player>>,
+    In(bodies): In<Vec<Body>>,
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Behaviour,
+            &Team,
+            &StatusEffects,
+            Option<&WindUp>,
+            Option<&Charge>,
+            Option<&Thief>,
+        ),
+        Without<Player>,
+    >,
     time: Res<Time>,
+    time_scale: Res<TimeScale>,
 ) {
-    let player_position = player_query.single().translation;
-    for (mut transform, behaviour) in &mut enemy_query {
-        let homing = (player_position - transform.translation).normalize();
-        let separation = other_positions
+    for (mut transform, behaviour, &team, effects, wind_up, charge, thief) in &mut enemy_query {
+        // Chargers hold still while lining up and during their dash
+        let holding = charge.is_some_and(|charge| {
+            wind_up.is_some_and(|wind_up| wind_up.active) || charge.is_dashing()
+        });
+        let target = nearest_opponent(&bodies, transform.translation, team);
+        let fleeing = thief.is_some_and(|thief| thief.stolen.is_some());
+        let homing = if fleeing {
+            nearest_edge(transform.translation)
+        } else {
+            target.map_or(Vec3::ZERO, |target| {
+                (target - transform.translation).normalize_or_zero()
+            })
+        };
+        let approach = if fleeing {
+            1.5 // Thieves make a run for the edge once they have a spark
+        } else if let (true, Some(target)) = (behaviour.keep_away > 0.0, target) {
+            let distance = target.distance(transform.translation);
+            ((distance - behaviour.keep_away) / 50.0).clamp(-1.0, 1.0)
+        } else {
+            1.0
+        };
+        let separation = bodies
             .iter()
-            .filter_map(|&pos| {
-                if pos == transform.translation {
+            .filter_map(|body| {
+                if !body.steering || body.position == transform.translation {
                     return None;
                 }
-                let away = transform.translation - pos;
+                let away = transform.translation - body.position;
                 let distance = away.length_squared();
                 Some(away.normalize() * distance.recip())
             })
             .sum::<Vec3>()
             .clamp_length_max(behaviour.separating_force.recip());
 
-        transform.translation += (homing * behaviour.homing_force
-            + separation * behaviour.separating_force.powi(2))
-            * time.delta_seconds();
+        let homing_force = if holding {
+            0.0
+        } else {
+            behaviour.homing_force * effects.speed_multiplier()
+        };
+        transform.translation += (homing * approach * homing_force
+            + separation * behaviour.separating_force.powi(2)
+            + effects.knockback().extend(0.0))
+            * time_scale.delta_seconds(&time, team);
+
+        if !effects.is_stunned() && !charge.is_some_and(Charge::is_dashing) && homing != Vec3::ZERO
+        {
+            transform.rotation = Quat::from_rotation_arc(Vec3::Y, homing);
+        }
+    }
+}
+
+fn handle_charges(
+    mut charger_query: Query<(&mut Transform, &Team, &mut Charge, &mut StatusEffects)>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    for (mut transform, &team, mut charge, mut effects) in &mut charger_query {
+        if !charge.is_dashing() {
+            continue;
+        }
+        if effects.is_stunned() {
+            charge.stop(); // Getting hit knocks a charger out of its dash
+            continue;
+        }
+
+        transform.translation +=
+            charge.direction * charge.speed * time_scale.delta_seconds(&time, team);
+        if charge
+            .timer
+            .tick(time_scale.delta(&time, team))
+            .just_finished()
+        {
+            effects.apply(StatusEffect::stun(1.2));
+        }
+    }
+}
+
+/// Hurts everything on the other team a charger runs into, once per dash.
+fn handle_charge_hits(
+    mut commands: Commands,
+    mut charger_query: Query<(Entity, &Transform, &Radius, &Team, &mut Charge)>,
+    mut target_query: Query<(&mut Health, Has<Player>)>,
+    colliders: Colliders,
+    mut hurt_event_writer: EventWriter<HurtPlayerEvent>,
+) {
+    for (enemy, transform, radius, team, mut charge) in &mut charger_query {
+        if !charge.is_dashing() {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        for hit in colliders.circle(position, radius.0, Some(team.opposite())) {
+            if charge.struck.contains(&hit.entity) {
+                continue;
+            }
+            charge.struck.push(hit.entity);
+            let Ok((mut health, is_player)) = target_query.get_mut(hit.entity) else {
+                continue;
+            };
+            if is_player {
+                hurt_event_writer.send(HurtPlayerEvent {
+                    source: Some(enemy),
+                    cause: DamageCause::Melee,
+                });
+            } else {
+                **health = health.saturating_sub(1);
+                commands.entity(hit.entity).try_insert(HitFlash::default());
+            }
+        }
+    }
+}
+
+/// Whoever an attack is aimed at.
+#[derive(Clone, Copy)]
+pub struct Target {
+    pub entity: Entity,
+    pub position: Vec3,
+    /// Only known for the player, everything else is assumed to be standing still.
+    pub velocity: Vec2,
+}
+
+/// Looks up the closest body an attacker should go after.
+#[derive(SystemParam)]
+struct Targets<'w, 's> {
+    query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Transform,
+            &'static Team,
+            Option<&'static PlayerVelocity>,
+        ),
+        With<Health>,
+    >,
+}
 
-        transform.rotation = Quat::from_rotation_arc(Vec3::Y, homing);
+impl Targets<'_, '_> {
+    /// The nearest body that isn't on `team`.
+    fn nearest(&self, position: Vec3, team: Team) -> Option<Target> {
+        self.query
+            .iter()
+            .filter(|(_, _, &other_team, _)| other_team != team)
+            .min_by(|(_, a, _, _), (_, b, _, _)| {
+                a.translation
+                    .distance_squared(position)
+                    .total_cmp(&b.translation.distance_squared(position))
+            })
+            .map(|(entity, transform, _, velocity)| Target {
+                entity,
+                position: transform.translation,
+                velocity: velocity.map_or(Vec2::ZERO, |velocity| **velocity),
+            })
     }
 }
 
 fn handle_attacks(
     mut commands: Commands,
-    mut enemy_query: Query<(Entity, &mut AttackTimer, &EnemyKind)>,
+    mut enemy_query: Query<(
+        Entity,
+        &Transform,
+        &Team,
+        &mut AttackTimer,
+        &mut WindUp,
+        &EnemyKind,
+        &StatusEffects,
+    )>,
+    targets: Targets,
     time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    for (enemy, transform, &team, mut timer, mut wind_up, &kind, effects) in &mut enemy_query {
+        // Stuns pause the wind-up rather than cancelling it, so hits can delay an attack but never stop it
+        if effects.is_stunned() {
+            continue;
+        }
+        let delta = time_scale.delta(&time, team);
+        let Some(target) = targets.nearest(transform.translation, team) else {
+            wind_up.active = false;
+            continue;
+        };
+
+        if wind_up.active {
+            wind_up.target = target.position;
+            if wind_up.timer.tick(delta).finished() {
+                wind_up.active = false;
+                commands.add(AttackCommand {
+                    enemy,
+                    kind,
+                    target,
+                });
+            }
+            continue;
+        }
+
+        if timer.tick(delta).finished()
+            && transform.translation.distance(target.position) <= wind_up.range
+        {
+            wind_up.active = true;
+            wind_up.target = target.position;
+            wind_up.timer.reset();
+            if let Some(sound) = &wind_up.sound {
+                commands.spawn(AudioBundle {
+                    source: sound.clone(),
+                    settings: PlaybackSettings::DESPAWN
+                        .with_volume(Volume::new_relative(0.3))
+                        .with_speed(1.5),
+                });
+            }
+        }
+    }
+}
+
+fn telegraph_attacks(
+    mut enemy_query: Query<(&mut Transform, &mut Sprite, &WindUp, &EnemyKind)>,
+    mut gizmos: Gizmos,
 ) {
-    for (enemy, mut timer, &kind) in &mut enemy_query {
-        if timer.tick(time.delta()).finished() {
-            commands.add(AttackCommand { enemy, kind });
+    for (mut transform, mut sprite, wind_up, kind) in &mut enemy_query {
+        if !wind_up.active {
+            transform.scale = Vec3::ONE;
+            continue;
+        }
+
+        let progress = wind_up.timer.percent();
+        let flash = 1.0 - 0.3 * (1.0 - (progress * 4.0 * PI).cos());
+        sprite.color = Color::rgba(
+            sprite.color.r(),
+            sprite.color.g() * flash,
+            sprite.color.b() * flash,
+            sprite.color.a(),
+        );
+        transform.scale = Vec3::splat(1.0 + 0.2 * progress);
+
+        if let EnemyKind::Ranged | EnemyKind::Boss | EnemyKind::Charger = kind {
+            let start = transform.translation.truncate();
+            gizmos.line_gradient_2d(
+                start,
+                wind_up.target.truncate(),
+                Color::rgba(1.0, 0.2, 0.2, 0.8 * progress),
+                Color::rgba(1.0, 0.2, 0.2, 0.0),
+            );
+        }
+    }
+}
+
+/// Charmed enemies fight for the player until the charm wears off.
+fn apply_charms(mut enemy_query: Query<(&mut Team, &mut WindUp, &StatusEffects), With<Enemy>>) {
+    for (mut team, mut wind_up, effects) in &mut enemy_query {
+        let allegiance = if effects.is_charmed() {
+            Team::Friendly
+        } else {
+            Team::Hostile
+        };
+        if team.set_if_neq(allegiance) {
+            wind_up.active = false; // Whatever it was lining up is no longer an enemy
         }
     }
 }
@@ -194,71 +936,246 @@ fn handle_attacks(
 struct AttackCommand {
     enemy: Entity,
     kind: EnemyKind,
+    target: Target,
 }
 
 impl Command for AttackCommand {
     fn apply(self, world: &mut World) {
+        let input = (self.enemy, self.target);
         match self.kind {
-            EnemyKind::Basic => world.run_system_once_with(self.enemy, handle_basic_attack),
-            EnemyKind::Ranged => world.run_system_once_with(self.enemy, handle_ranged_attack),
+            EnemyKind::Basic => world.run_system_once_with(input, handle_basic_attack),
+            EnemyKind::Ranged => world.run_system_once_with(input, handle_ranged_attack),
+            EnemyKind::Boss => world.run_system_once_with(input, boss::handle_boss_attack),
+            EnemyKind::Splitter | EnemyKind::Shielded | EnemyKind::Bomber => {
+                world.run_system_once_with(input, handle_basic_attack)
+            }
+            EnemyKind::Charger => world.run_system_once_with(input, handle_charger_attack),
+            EnemyKind::Healer => world.run_system_once_with(input, handle_healer_attack),
+            EnemyKind::Thief => world.run_system_once_with(input, handle_thief_attack),
         }
     }
 }
 
 fn handle_basic_attack(
-    In(enemy): In<Entity>,
+    In((enemy, target)): In<(Entity, Target)>,
+    mut commands: Commands,
     enemy_query: Query<(&Transform, &Radius), With<Enemy>>,
-    player_query: Query<(&Transform, &Radius), With<Player>>,
+    mut target_query: Query<(
+        &Transform,
+        &Radius,
+        &mut Health,
+        &mut StatusEffects,
+        Has<Player>,
+    )>,
     mut hurt_event_writer: EventWriter<HurtPlayerEvent>,
 ) {
     let Ok((enemy_transform, enemy_radius)) = enemy_query.get(enemy) else {
         return; // Enemy died before command was executed
     };
-    let (player_transform, player_radius) = player_query.single();
+    let Ok((target_transform, target_radius, mut health, mut effects, is_player)) =
+        target_query.get_mut(target.entity)
+    else {
+        return;
+    };
+
+    let offset = (target_transform.translation - enemy_transform.translation).truncate();
+    if offset.length_squared() > (enemy_radius.0 + target_radius.0).powi(2) {
+        return;
+    }
+    if is_player {
+        hurt_event_writer.send(HurtPlayerEvent {
+            source: Some(enemy),
+            cause: DamageCause::Melee,
+        });
+    } else {
+        **health = health.saturating_sub(1);
+        commands
+            .entity(target.entity)
+            .try_insert(HitFlash::default());
+    }
+    effects.apply(StatusEffect::knockback(
+        offset.normalize_or_zero() * 600.0,
+        0.2,
+    ));
+}
+
+fn handle_charger_attack(
+    In((enemy, target)): In<(Entity, Target)>,
+    mut enemy_query: Query<(&mut Transform, &mut Charge), With<Enemy>>,
+) {
+    let Ok((mut transform, mut charge)) = enemy_query.get_mut(enemy) else {
+        return; // Enemy died before command was executed
+    };
+
+    let Some(direction) = (target.position - transform.translation).try_normalize() else {
+        return;
+    };
+    charge.direction = direction;
+    charge.struck.clear();
+    charge.timer.reset();
+    transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction);
+}
+
+/// Heals every damaged ally within reach of a support enemy.
+fn handle_healer_attack(
+    In((enemy, _)): In<(Entity, Target)>,
+    mut commands: Commands,
+    healer_query: Query<(&Transform, &Team, &Support)>,
+    mut enemy_query: Query<(Entity, &Transform, &Team, &mut Health, &MaxHealth), With<Enemy>>,
+) {
+    let Ok((healer_transform, healer_team, support)) = healer_query.get(enemy) else {
+        return; // Enemy died before command was executed
+    };
+
+    for (other, transform, team, mut health, max_health) in &mut enemy_query {
+        if other == enemy
+            || team != healer_team
+            || **health >= **max_health
+            || transform.translation.distance(healer_transform.translation) > support.radius
+        {
+            continue;
+        }
+        **health += 1;
+        commands.entity(other).try_insert(HitFlash::default());
+    }
+}
 
-    if enemy_transform
-        .translation
-        .distance_squared(player_transform.translation)
-        <= (enemy_radius.0 + player_radius.0).powi(2)
+fn draw_tethers(
+    healer_query: Query<(Entity, &Transform, &Support, &WindUp)>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut gizmos: Gizmos,
+) {
+    for (healer, healer_transform, support, wind_up) in &healer_query {
+        let start = healer_transform.translation.truncate();
+        let alpha = if wind_up.active {
+            0.3 + 0.7 * wind_up.timer.percent()
+        } else {
+            0.3
+        };
+        for (other, transform) in &enemy_query {
+            let end = transform.translation.truncate();
+            if other == healer || start.distance(end) > support.radius {
+                continue;
+            }
+            gizmos.line_2d(start, end, Color::rgba(0.6, 1.0, 0.6, alpha));
+        }
+    }
+}
+
+fn handle_thief_attack(
+    In((enemy, target)): In<(Entity, Target)>,
+    mut thief_query: Query<(&Transform, &Radius, &mut Thief), With<Enemy>>,
+    mut player_query: Query<(&Transform, &Radius, &mut Sparks), With<Player>>,
+    mut hurt_event_writer: EventWriter<HurtPlayerEvent>,
+) {
+    let Ok((thief_transform, thief_radius, mut thief)) = thief_query.get_mut(enemy) else {
+        return; // Enemy died before command was executed
+    };
+    let Ok((player_transform, player_radius, mut sparks)) = player_query.get_mut(target.entity)
+    else {
+        return; // Only the player has anything worth stealing
+    };
+
+    if thief.stolen.is_some()
+        || thief_transform
+            .translation
+            .distance_squared(player_transform.translation)
+            > (thief_radius.0 + player_radius.0).powi(2)
     {
-        hurt_event_writer.send(HurtPlayerEvent);
+        return;
+    }
+
+    match sparks.pop_front() {
+        Some(spark) => thief.stolen = Some(spark),
+        None => hurt_event_writer.send(HurtPlayerEvent {
+            source: Some(enemy),
+            cause: DamageCause::Melee,
+        }),
+    }
+}
+
+/// The direction of the arena edge closest to `position`.
+fn nearest_edge(position: Vec3) -> Vec3 {
+    if position.x.abs() > position.y.abs() {
+        Vec3::X * position.x.signum()
+    } else {
+        Vec3::Y * position.y.signum()
+    }
+}
+
+/// Thieves that make it off screen get away with their spark.
+fn handle_escapes(
+    mut commands: Commands,
+    thief_query: Query<(Entity, &Transform, &Radius, &Thief)>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, transform, radius, thief) in &thief_query {
+        if thief.stolen.is_none() {
+            continue;
+        }
+        if transform.translation.abs().max_element() > ARENA_EXTENT + radius.0 {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            gizmos.circle_2d(
+                transform.translation.truncate(),
+                radius.0 + 5.0,
+                Color::rgb(1.0, 0.85, 0.3),
+            );
+        }
     }
 }
 
 fn handle_ranged_attack(
-    In(enemy): In<Entity>,
+    In((enemy, target)): In<(Entity, Target)>,
     mut commands: Commands,
-    enemy_query: Query<&Transform, With<Enemy>>,
-    player_query: Query<&Transform, With<Player>>,
-    assets: Res<GameAssets>,
+    enemy_query: Query<(&Transform, &Team, &Aim), With<Enemy>>,
 ) {
-    let Ok(enemy_transform) = enemy_query.get(enemy) else {
+    let Ok((enemy_transform, &team, aim)) = enemy_query.get(enemy) else {
         return; // Enemy died before command was executed
     };
-    let player_transform = player_query.single();
-
-    let mut projectile_transform = *enemy_transform;
-    let direction = (player_transform.translation - enemy_transform.translation)
-        .truncate()
-        .normalize();
-    projectile_transform.rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
-
-    commands.spawn(ProjectileBundle {
-        projectile: Projectile,
-        game: Game,
-        sprite: SpriteBundle {
-            texture: assets.bullet.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2 { x: 25.0, y: 25.0 }),
-                ..Default::default()
-            },
-            transform: projectile_transform,
-            ..Default::default()
-        },
-        velocity: Velocity(150.0),
-        team: Team::Hostile,
-        radius: Radius(12.5),
-    });
+    let speed = 150.0;
+
+    let offset = (target.position - enemy_transform.translation).truncate();
+    let direction = lead_target(offset, target.velocity * aim.lead, speed).normalize_or_zero();
+    let spread = (1.0 - aim.accuracy) * FRAC_PI_2;
+    let deviation = rand::thread_rng().gen_range(-spread..=spread);
+    let rotation =
+        Quat::from_rotation_z(deviation) * Quat::from_rotation_arc_2d(Vec2::Y, direction);
+
+    // Shots turn gently towards their target, so a late sidestep is needed to dodge them
+    BulletPattern::single(speed)
+        .bullet(25.0, Color::WHITE)
+        .motion(Motion::default().homing(0.5))
+        .inflicts(StatusEffect::slow(0.6, 1.0))
+        .emit(&mut commands, enemy, rotation, team);
+}
+
+/// Finds where a projectile fired at `speed` would meet a target at `offset` moving at `velocity`.
+/// Falls back to the target's current position if it can't be caught.
+pub fn lead_target(offset: Vec2, velocity: Vec2, speed: f32) -> Vec2 {
+    let a = velocity.length_squared() - speed.powi(2);
+    let b = 2.0 * offset.dot(velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        -c / b
+    } else {
+        let discriminant = b.powi(2) - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return offset;
+        }
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+            .into_iter()
+            .filter(|&t| t > 0.0)
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    if time.is_finite() && time > 0.0 {
+        offset + velocity * time
+    } else {
+        offset
+    }
 }
 
 fn despawn_puffs(
@@ -0,0 +1,155 @@
+use std::f32::consts::PI;
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+use crate::{GameAssets, GameState};
+
+use super::{
+    health::Health,
+    player::{DamageCause, HurtPlayerEvent, Player},
+    projectile::{Radius, Team},
+    raycast::Colliders,
+    status::{HitFlash, StatusEffects},
+    Game,
+};
+
+/// Damages everything within `radius` of `position`, hitting hardest at the centre.
+#[derive(Event)]
+pub struct ExplosionEvent {
+    pub position: Vec3,
+    pub radius: f32,
+    pub damage: usize,
+    /// Members of this team are left unharmed, explosions without a team hurt everyone.
+    pub team: Option<Team>,
+}
+
+/// An expanding ring showing where an explosion hit.
+#[derive(Component)]
+struct Shockwave {
+    radius: f32,
+    timer: Timer,
+}
+
+/// A lobbed projectile that sails over enemies and explodes when its fuse runs out.
+#[derive(Component)]
+pub struct Bomb {
+    pub fuse: Timer,
+    pub radius: f32,
+    pub damage: usize,
+}
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>().add_systems(
+            Update,
+            (handle_explosions, update_bombs, draw_shockwaves).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn handle_explosions(
+    mut commands: Commands,
+    mut explosion_events: EventReader<ExplosionEvent>,
+    mut target_query: Query<(
+        &Radius,
+        &Team,
+        &mut Health,
+        Option<&mut StatusEffects>,
+        Has<Player>,
+    )>,
+    colliders: Colliders,
+    mut hurt_event_writer: EventWriter<HurtPlayerEvent>,
+    assets: Res<GameAssets>,
+) {
+    for explosion in explosion_events.read() {
+        commands.spawn((
+            TransformBundle::from_transform(Transform::from_translation(explosion.position)),
+            Shockwave {
+                radius: explosion.radius,
+                timer: Timer::from_seconds(0.3, TimerMode::Once),
+            },
+            Game,
+        ));
+        commands.spawn(AudioBundle {
+            source: assets.hit_enemy.clone(),
+            settings: PlaybackSettings::DESPAWN
+                .with_speed(0.5)
+                .with_volume(Volume::new_relative(1.5)),
+        });
+
+        let center = explosion.position.truncate();
+        for hit in colliders.circle(center, explosion.radius, None) {
+            let Ok((radius, team, mut health, effects, is_player)) =
+                target_query.get_mut(hit.entity)
+            else {
+                continue;
+            };
+            if Some(*team) == explosion.team || **health == 0 {
+                continue;
+            }
+            let offset = hit.position - center;
+            let reach = explosion.radius + radius.0;
+
+            let falloff = 1.0 - offset.length() / reach;
+            if is_player {
+                hurt_event_writer.send(HurtPlayerEvent {
+                    source: None,
+                    cause: DamageCause::Explosion,
+                });
+            } else {
+                let damage = ((explosion.damage as f32 * falloff).ceil() as usize).max(1);
+                **health = health.saturating_sub(damage);
+                commands.entity(hit.entity).try_insert(HitFlash::default());
+            }
+            if let Some(mut effects) = effects {
+                effects.hit(offset.normalize_or_zero() * 1500.0 * falloff, 0.2);
+            }
+        }
+    }
+}
+
+fn update_bombs(
+    mut commands: Commands,
+    mut bomb_query: Query<(Entity, &mut Bomb, &mut Transform)>,
+    mut explosion_writer: EventWriter<ExplosionEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut bomb, mut transform) in &mut bomb_query {
+        // Grow and shrink to look like it's arcing through the air
+        let height = (bomb.fuse.percent() * PI).sin();
+        transform.scale = Vec3::splat(1.0 + 0.8 * height);
+
+        if bomb.fuse.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            explosion_writer.send(ExplosionEvent {
+                position: transform.translation,
+                radius: bomb.radius,
+                damage: bomb.damage,
+                team: Some(Team::Friendly),
+            });
+        }
+    }
+}
+
+fn draw_shockwaves(
+    mut commands: Commands,
+    mut shockwave_query: Query<(Entity, &Transform, &mut Shockwave)>,
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, transform, mut shockwave) in &mut shockwave_query {
+        if shockwave.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = shockwave.timer.percent();
+        gizmos.circle_2d(
+            transform.translation.truncate(),
+            shockwave.radius * progress,
+            Color::rgba(1.0, 0.6, 0.2, 1.0 - progress),
+        );
+    }
+}
@@ -4,9 +4,16 @@ use crate::{GameAssets, GameState};
 
 use super::{player::Player, Game};
 
+/// Colour of the containers for hearts the player has lost.
+const EMPTY_HEART: Color = Color::rgba(0.2, 0.2, 0.2, 0.6);
+
 #[derive(Component, Deref, DerefMut)]
 pub struct Health(pub usize);
 
+/// The most health an entity can be healed up to.
+#[derive(Component, Deref, DerefMut)]
+pub struct MaxHealth(pub usize);
+
 #[derive(Component)]
 struct HealthDisplay;
 
@@ -40,11 +47,14 @@ fn spawn_health_display(mut commands: Commands) {
 
 fn update_health_display(
     mut commands: Commands,
-    health_query: Query<&Health, (Changed<Health>, With<Player>)>,
+    health_query: Query<
+        (&Health, &MaxHealth),
+        (Or<(Changed<Health>, Changed<MaxHealth>)>, With<Player>),
+    >,
     health_display_query: Query<Entity, With<HealthDisplay>>,
     assets: Res<GameAssets>,
 ) {
-    let Ok(health) = health_query.get_single() else {
+    let Ok((health, max_health)) = health_query.get_single() else {
         return; // health didn't change, don't bother updating
     };
 
@@ -54,7 +64,7 @@ fn update_health_display(
         .entity(health_display)
         .despawn_descendants()
         .with_children(|parent| {
-            for _ in 0..health.0 {
+            for i in 0..max_health.0.max(health.0) {
                 parent.spawn(ImageBundle {
                     style: Style {
                         width: Val::Px(40.0),
@@ -63,6 +73,11 @@ fn update_health_display(
                         ..Default::default()
                     },
                     image: UiImage::new(assets.heart.clone()),
+                    background_color: if i < health.0 {
+                        Color::WHITE.into()
+                    } else {
+                        EMPTY_HEART.into()
+                    },
                     ..Default::default()
                 });
             }
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use crate::{GameAssets, GameState};
+
+use super::{
+    enemy::Behaviour,
+    health::Health,
+    projectile::{Radius, Team},
+    raycast::Colliders,
+    status::{HitFlash, StatusEffects, Tint},
+    time_scale::TimeScale,
+    Game,
+};
+
+/// A summoned body that fights for the player by ramming enemies.
+/// It fades away after its lifetime or once it has landed enough hits.
+#[derive(Component)]
+struct Minion {
+    lifetime: Timer,
+    hits_left: usize,
+    cooldown: Timer,
+}
+
+#[derive(Bundle)]
+pub struct MinionBundle {
+    minion: Minion,
+    game: Game,
+    #[bundle()]
+    sprite: SpriteBundle,
+    team: Team,
+    health: Health,
+    radius: Radius,
+    behaviour: Behaviour,
+    status_effects: StatusEffects,
+    tint: Tint,
+}
+
+impl MinionBundle {
+    pub fn ghost(position: Vec3, assets: &GameAssets) -> Self {
+        Self {
+            minion: Minion {
+                lifetime: Timer::from_seconds(8.0, TimerMode::Once),
+                hits_left: 3,
+                cooldown: Timer::from_seconds(0.5, TimerMode::Once),
+            },
+            game: Game,
+            sprite: SpriteBundle {
+                texture: assets.basic_enemy.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2 { x: 30.0, y: 30.0 }),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            },
+            team: Team::Friendly,
+            health: Health(1),
+            radius: Radius(15.0),
+            behaviour: Behaviour::new(130.0, 60.0, 0.0),
+            status_effects: StatusEffects::default(),
+            tint: Tint(Color::rgba(0.7, 0.9, 1.0, 0.7)),
+        }
+    }
+}
+
+pub struct MinionPlugin;
+
+impl Plugin for MinionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (handle_minion_contacts, expire_minions).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn handle_minion_contacts(
+    mut commands: Commands,
+    mut minion_query: Query<(&Transform, &Radius, &Team, &mut Minion)>,
+    mut target_query: Query<(&mut Health, Option<&mut StatusEffects>)>,
+    colliders: Colliders,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    for (transform, radius, &team, mut minion) in &mut minion_query {
+        if !minion
+            .cooldown
+            .tick(time_scale.delta(&time, team))
+            .finished()
+            || minion.hits_left == 0
+        {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let Some(hit) = colliders
+            .circle(position, radius.0, Some(team.opposite()))
+            .first()
+            .copied()
+        else {
+            continue;
+        };
+        let Ok((mut health, effects)) = target_query.get_mut(hit.entity) else {
+            continue;
+        };
+
+        **health = health.saturating_sub(1);
+        commands.entity(hit.entity).try_insert(HitFlash::default());
+        if let Some(mut effects) = effects {
+            effects.hit((hit.position - position).normalize_or_zero() * 500.0, 0.2);
+        }
+        minion.hits_left -= 1;
+        minion.cooldown.reset();
+    }
+}
+
+fn expire_minions(
+    mut commands: Commands,
+    mut minion_query: Query<(Entity, &Team, &mut Minion, &Health, &mut Tint)>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    for (entity, &team, mut minion, health, mut tint) in &mut minion_query {
+        minion.lifetime.tick(time_scale.delta(&time, team));
+        if minion.lifetime.finished() || minion.hits_left == 0 || **health == 0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Fade out over the last second
+        let remaining = minion.lifetime.remaining_secs().min(1.0);
+        tint.0.set_a(0.7 * remaining);
+    }
+}
@@ -6,21 +6,36 @@ use rand::distributions::{Distribution, Uniform};
 use crate::{utils, GameAssets, GameState};
 
 use self::{
+    affix::Affixes,
+    boss::Boss,
     enemy::{EnemyBundle, EnemyKind},
-    score::Score,
+    stats::RunStats,
 };
 
+mod affix;
+mod boss;
 mod enemy;
+mod explosion;
 mod health;
+mod minion;
+mod pattern;
+mod pickup;
 mod player;
 mod pointer;
 mod projectile;
+mod raycast;
 pub mod score;
 mod spark;
+pub mod stats;
+mod status;
+mod time_scale;
 
 #[derive(Component)]
 struct Game;
 
+/// Half the width of the square arena, which fills the window.
+const ARENA_EXTENT: f32 = 400.0;
+
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -33,7 +48,16 @@ impl Plugin for GamePlugin {
             score::ScorePlugin,
             health::HealhPlugin,
             pointer::PointerPlugin,
+            status::StatusPlugin,
+            stats::StatsPlugin,
+            boss::BossPlugin,
+            affix::AffixPlugin,
+            explosion::ExplosionPlugin,
+            pattern::PatternPlugin,
+            minion::MinionPlugin,
+            time_scale::TimeScalePlugin,
         ))
+        .add_plugins(pickup::PickupPlugin)
         .add_systems(OnExit(GameState::Playing), utils::despawn_with::<Game>)
         .add_systems(Update, spawn_enemy.run_if(in_state(GameState::Playing)));
     }
@@ -43,22 +67,36 @@ fn spawn_enemy(
     mut commands: Commands,
     assets: Res<GameAssets>,
     time: Res<Time>,
-    score: Res<Score>,
+    stats: Res<RunStats>,
+    boss_query: Query<(), With<Boss>>,
 ) {
+    if !boss_query.is_empty() {
+        return; // Regular spawns are paused during boss fights
+    }
+    let difficulty = stats.difficulty() as f32;
+
     let mut rng = rand::thread_rng();
     let uniform = Uniform::new(0.0, 1.0);
 
     for (kind, per_second) in [
         (EnemyKind::Basic, 1.0),
-        (EnemyKind::Ranged, 0.01 * score.score as f32),
+        (EnemyKind::Ranged, 0.01 * difficulty),
+        (EnemyKind::Splitter, 0.005 * difficulty),
+        (EnemyKind::Charger, 0.005 * difficulty),
+        (EnemyKind::Shielded, 0.004 * difficulty),
+        (EnemyKind::Healer, 0.003 * difficulty),
+        (EnemyKind::Thief, 0.003 * difficulty),
+        (EnemyKind::Bomber, 0.004 * difficulty),
     ] {
         if uniform.sample(&mut rng) <= per_second * time.delta_seconds() {
             let position =
                 Quat::from_rotation_z(uniform.sample(&mut rng) * TAU).mul_vec3(Vec3::Y * 600.0);
 
-            commands
-                .spawn(EnemyBundle::new(kind, &assets))
-                .insert(Transform::from_translation(position));
+            let mut enemy = EnemyBundle::spawn(&mut commands, kind, &assets);
+            enemy.insert(Transform::from_translation(position));
+            if let Some(affixes) = Affixes::roll(stats.difficulty()) {
+                enemy.insert(affixes);
+            }
         }
     }
 }
@@ -0,0 +1,310 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{GameAssets, GameState};
+
+use super::{
+    player::Player,
+    projectile::{
+        Inflicts, Motion, PlayerShot, Projectile, ProjectileBundle, Radius, SplitOnImpact, Team,
+        Velocity,
+    },
+    stats::RunStats,
+    status::StatusEffect,
+    time_scale::TimeScale,
+    Game,
+};
+
+/// Describes a volley of bullets, from a single shot up to spiralling multi-burst rings.
+#[derive(Clone)]
+pub struct BulletPattern {
+    /// Bullets fired in each burst.
+    count: usize,
+    /// The angle each burst is spread across, a full turn makes a ring.
+    spread: f32,
+    /// Extra rotation added after every bullet, carried across bursts.
+    rotation_per_shot: f32,
+    bursts: usize,
+    burst_interval: f32,
+    /// Each bullet picks a random speed from this range.
+    speed: (f32, f32),
+    /// Extra rotation added after every burst, for spirals.
+    spiral_offset: f32,
+    size: f32,
+    color: Color,
+    motion: Option<Motion>,
+    inflicts: Option<StatusEffect>,
+    split: Option<usize>,
+}
+
+impl BulletPattern {
+    /// A single bullet fired straight ahead.
+    pub fn single(speed: f32) -> Self {
+        Self {
+            count: 1,
+            spread: 0.0,
+            rotation_per_shot: 0.0,
+            bursts: 1,
+            burst_interval: 0.0,
+            speed: (speed, speed),
+            spiral_offset: 0.0,
+            size: 19.0,
+            color: Color::WHITE,
+            motion: None,
+            inflicts: None,
+            split: None,
+        }
+    }
+
+    /// `count` bullets fanned evenly across `spread` radians.
+    pub fn spread(count: usize, spread: f32, speed: f32) -> Self {
+        Self {
+            count,
+            spread,
+            ..Self::single(speed)
+        }
+    }
+
+    /// `count` bullets evenly spaced in every direction.
+    pub fn ring(count: usize, speed: f32) -> Self {
+        Self::spread(count, TAU, speed)
+    }
+
+    /// Repeats the pattern `bursts` times, `interval` seconds apart.
+    pub fn bursts(self, bursts: usize, interval: f32) -> Self {
+        Self {
+            bursts,
+            burst_interval: interval,
+            ..self
+        }
+    }
+
+    /// Rotates each burst by `offset` radians more than the last.
+    pub fn spiral(self, offset: f32) -> Self {
+        Self {
+            spiral_offset: offset,
+            ..self
+        }
+    }
+
+    /// Rotates each bullet by `rotation` radians more than the last.
+    pub fn rotating(self, rotation: f32) -> Self {
+        Self {
+            rotation_per_shot: rotation,
+            ..self
+        }
+    }
+
+    /// Gives each bullet a random speed between `min` and `max`.
+    pub fn speeds(self, min: f32, max: f32) -> Self {
+        Self {
+            speed: (min, max),
+            ..self
+        }
+    }
+
+    pub fn bullet(self, size: f32, color: Color) -> Self {
+        Self {
+            size,
+            color,
+            ..self
+        }
+    }
+
+    pub fn motion(self, motion: Motion) -> Self {
+        Self {
+            motion: Some(motion),
+            ..self
+        }
+    }
+
+    pub fn inflicts(self, effect: StatusEffect) -> Self {
+        Self {
+            inflicts: Some(effect),
+            ..self
+        }
+    }
+
+    /// Bullets burst into a fan of `count` more when they hit something.
+    pub fn splits(self, count: usize) -> Self {
+        Self {
+            split: Some(count),
+            ..self
+        }
+    }
+
+    /// Fires the pattern from `source`, facing `rotation`, for as long as `source` is alive.
+    pub fn emit(self, commands: &mut Commands, source: Entity, rotation: Quat, team: Team) {
+        commands.spawn((
+            Emitter {
+                timer: Timer::from_seconds(self.burst_interval, TimerMode::Repeating),
+                pattern: self,
+                source,
+                rotation,
+                team,
+                bursts_fired: 0,
+                shots_fired: 0,
+            },
+            Game,
+        ));
+    }
+
+    fn angle(&self, index: usize) -> f32 {
+        if self.count <= 1 {
+            0.0
+        } else if self.spread >= TAU {
+            TAU * index as f32 / self.count as f32
+        } else {
+            self.spread * (index as f32 / (self.count - 1) as f32 - 0.5)
+        }
+    }
+}
+
+/// Fires the bursts of a pattern over time.
+#[derive(Component)]
+struct Emitter {
+    pattern: BulletPattern,
+    source: Entity,
+    rotation: Quat,
+    team: Team,
+    timer: Timer,
+    bursts_fired: usize,
+    shots_fired: usize,
+}
+
+pub struct PatternPlugin;
+
+impl Plugin for PatternPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, run_emitters.run_if(in_state(GameState::Playing)));
+    }
+}
+
+fn run_emitters(
+    mut commands: Commands,
+    mut emitter_query: Query<(Entity, &mut Emitter)>,
+    source_query: Query<(&Transform, Has<Player>)>,
+    mut stats: ResMut<RunStats>,
+    assets: Res<GameAssets>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    let mut rng = rand::thread_rng();
+    for (entity, mut emitter) in &mut emitter_query {
+        let delta = time_scale.delta(&time, emitter.team);
+        if emitter.bursts_fired > 0 && !emitter.timer.tick(delta).just_finished() {
+            continue;
+        }
+        let Ok((source, from_player)) = source_query.get(emitter.source) else {
+            commands.entity(entity).despawn_recursive();
+            continue; // Whatever was firing has died
+        };
+
+        let pattern = emitter.pattern.clone();
+        let burst_rotation = pattern.spiral_offset * emitter.bursts_fired as f32;
+        for i in 0..pattern.count {
+            let angle = pattern.angle(i)
+                + burst_rotation
+                + pattern.rotation_per_shot * emitter.shots_fired as f32;
+            let transform = Transform::from_translation(source.translation)
+                .with_rotation(emitter.rotation * Quat::from_rotation_z(angle));
+            let (min_speed, max_speed) = pattern.speed;
+            let speed = if min_speed < max_speed {
+                rng.gen_range(min_speed..max_speed)
+            } else {
+                min_speed
+            };
+
+            let mut bullet = commands.spawn(ProjectileBundle {
+                projectile: Projectile,
+                game: Game,
+                sprite: SpriteBundle {
+                    texture: assets.bullet.clone(),
+                    sprite: Sprite {
+                        color: pattern.color,
+                        custom_size: Some(Vec2::splat(pattern.size)),
+                        ..Default::default()
+                    },
+                    transform,
+                    ..Default::default()
+                },
+                velocity: Velocity::forward(&transform, speed),
+                team: emitter.team,
+                radius: Radius(pattern.size / 2.0),
+            });
+            if let Some(motion) = pattern.motion {
+                bullet.insert(motion);
+            }
+            if let Some(effect) = &pattern.inflicts {
+                bullet.insert(Inflicts(effect.clone()));
+            }
+            if let Some(count) = pattern.split {
+                bullet.insert(SplitOnImpact(count));
+            }
+            if from_player {
+                bullet.insert(PlayerShot);
+                stats.shots_fired += 1;
+            }
+            emitter.shots_fired += 1;
+        }
+
+        emitter.bursts_fired += 1;
+        if emitter.bursts_fired >= pattern.bursts {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    #[test]
+    fn single_fires_straight_ahead() {
+        assert_eq!(BulletPattern::single(100.0).angle(0), 0.0);
+    }
+
+    #[test]
+    fn spread_fans_out_evenly_around_the_aim() {
+        let pattern = BulletPattern::spread(3, FRAC_PI_2, 100.0);
+        let angles = (0..3).map(|i| pattern.angle(i)).collect::<Vec<_>>();
+        assert_eq!(angles, [-FRAC_PI_2 / 2.0, 0.0, FRAC_PI_2 / 2.0]);
+    }
+
+    #[test]
+    fn ring_doesnt_double_up_on_the_last_bullet() {
+        let pattern = BulletPattern::ring(4, 100.0);
+        let angles = (0..4).map(|i| pattern.angle(i)).collect::<Vec<_>>();
+        assert_eq!(angles, [0.0, TAU / 4.0, TAU / 2.0, 3.0 * TAU / 4.0]);
+    }
+
+    #[test]
+    fn emit_spawns_an_emitter_for_the_source() {
+        let mut world = World::new();
+        let source = world.spawn_empty().id();
+
+        let mut state = SystemState::<Commands>::new(&mut world);
+        let mut commands = state.get_mut(&mut world);
+        BulletPattern::ring(8, 100.0)
+            .bursts(3, 0.2)
+            .motion(Motion::default().homing(1.0))
+            .emit(&mut commands, source, Quat::IDENTITY, Team::Hostile);
+        state.apply(&mut world);
+
+        let mut query = world.query_filtered::<&Emitter, With<Game>>();
+        let emitter = query.single(&world);
+        assert_eq!(emitter.source, source);
+        assert!(emitter.team == Team::Hostile);
+        assert_eq!(emitter.pattern.count, 8);
+        assert_eq!(emitter.pattern.bursts, 3);
+        assert!(emitter.pattern.motion.is_some());
+        assert_eq!(emitter.timer.duration().as_secs_f32(), 0.2);
+        assert_eq!(emitter.bursts_fired, 0);
+    }
+}
@@ -0,0 +1,270 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{GameAssets, GameState};
+
+use super::{
+    enemy::EnemyKilledEvent,
+    health::{Health, MaxHealth},
+    player::{Player, PlayerDamagedEvent, Sparks},
+    projectile::{Radius, Shield},
+    score::{Score, ScoreSource},
+    spark::{spark_icon, Barrier, SparkKind},
+    stats::RunStats,
+    Game,
+};
+
+/// How long a dropped spark lies around before it fades.
+const ORB_LIFETIME: f32 = 8.0;
+
+/// How long a dropped heart lies around before it fades.
+const HEART_LIFETIME: f32 = 10.0;
+
+/// Pickups blink for this long before they disappear.
+const BLINK_TIME: f32 = 2.0;
+
+/// Something lying on the ground that the player has to walk over to collect.
+#[derive(Component)]
+struct Pickup {
+    lifetime: Timer,
+}
+
+/// A spark dropped by a dead enemy.
+#[derive(Component)]
+struct SparkOrb(SparkKind);
+
+/// Restores a heart, or pays out something else if the player is already at full health.
+#[derive(Component)]
+struct Heart;
+
+/// Kills since the player was last hurt, which make hearts more likely to drop.
+#[derive(Resource, Default)]
+struct KillStreak(usize);
+
+#[derive(Bundle)]
+pub struct SparkOrbBundle {
+    pickup: Pickup,
+    orb: SparkOrb,
+    game: Game,
+    #[bundle()]
+    sprite: SpriteBundle,
+    radius: Radius,
+}
+
+impl SparkOrbBundle {
+    pub fn new(kind: SparkKind, position: Vec3, assets: &GameAssets) -> Self {
+        let (texture, color) = spark_icon(kind, assets);
+        Self {
+            pickup: Pickup {
+                lifetime: Timer::from_seconds(ORB_LIFETIME, TimerMode::Once),
+            },
+            orb: SparkOrb(kind),
+            game: Game,
+            sprite: SpriteBundle {
+                texture,
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2 { x: 28.0, y: 28.0 }),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.truncate().extend(-1.0)),
+                ..Default::default()
+            },
+            radius: Radius(14.0),
+        }
+    }
+}
+
+fn heart_bundle(position: Vec3, assets: &GameAssets) -> impl Bundle {
+    (
+        Pickup {
+            lifetime: Timer::from_seconds(HEART_LIFETIME, TimerMode::Once),
+        },
+        Heart,
+        Game,
+        SpriteBundle {
+            texture: assets.heart.clone(),
+            sprite: Sprite {
+                custom_size: Some(Vec2 { x: 26.0, y: 26.0 }),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.truncate().extend(-1.0)),
+            ..Default::default()
+        },
+        Radius(13.0),
+    )
+}
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillStreak>()
+            .add_systems(OnEnter(GameState::Playing), reset_kill_streak)
+            .add_systems(
+                Update,
+                (
+                    attract_pickups,
+                    (collect_orbs, collect_hearts).after(attract_pickups),
+                    expire_pickups,
+                    drop_hearts,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_kill_streak(mut streak: ResMut<KillStreak>) {
+    *streak = KillStreak::default();
+}
+
+/// Pulls pickups towards the player once they're within the player's magnet radius.
+fn attract_pickups(
+    mut pickup_query: Query<&mut Transform, (With<Pickup>, Without<Player>)>,
+    player_query: Query<(&Transform, &Player)>,
+    time: Res<Time>,
+) {
+    let (player_transform, player) = player_query.single();
+    let target = player_transform.translation.truncate();
+    for mut transform in &mut pickup_query {
+        let offset = target - transform.translation.truncate();
+        let distance = offset.length();
+        if distance > player.magnet_radius {
+            continue;
+        }
+        // Speed up the closer the pickup gets, so it never trails behind a fleeing player
+        let speed = 150.0 + 450.0 * (1.0 - distance / player.magnet_radius);
+        let step = (speed * time.delta_seconds()).min(distance);
+        transform.translation += (offset.normalize_or_zero() * step).extend(0.0);
+    }
+}
+
+fn collect_orbs(
+    mut commands: Commands,
+    orb_query: Query<(Entity, &Transform, &Radius, &SparkOrb)>,
+    mut player_query: Query<(&Transform, &Radius, &mut Sparks), With<Player>>,
+    mut stats: ResMut<RunStats>,
+    assets: Res<GameAssets>,
+) {
+    let (player_transform, player_radius, mut sparks) = player_query.single_mut();
+    for (entity, transform, radius, orb) in &orb_query {
+        let distance = transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate());
+        if distance > radius.0 + player_radius.0 {
+            continue;
+        }
+        sparks.push_back(orb.0);
+        *stats.sparks_collected.entry(orb.0).or_default() += 1;
+        commands.entity(entity).despawn_recursive();
+        commands.spawn(AudioBundle {
+            source: assets.shoot.clone(),
+            settings: PlaybackSettings::DESPAWN.with_speed(2.0),
+        });
+    }
+}
+
+/// Heals the player, anything past their max health becomes a brief shield, or score if already shielded.
+fn collect_hearts(
+    mut commands: Commands,
+    heart_query: Query<(Entity, &Transform, &Radius), With<Heart>>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Radius,
+            &mut Health,
+            &MaxHealth,
+            Has<Shield>,
+        ),
+        With<Player>,
+    >,
+    mut score: ResMut<Score>,
+    assets: Res<GameAssets>,
+) {
+    let (player, player_transform, player_radius, mut health, max_health, mut shielded) =
+        player_query.single_mut();
+    for (entity, transform, radius) in &heart_query {
+        let distance = transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate());
+        if distance > radius.0 + player_radius.0 {
+            continue;
+        }
+
+        if **health < **max_health {
+            **health += 1;
+        } else if !shielded {
+            commands.entity(player).insert((
+                Shield {
+                    arc: PI,
+                    reflect: false,
+                },
+                Barrier(Timer::from_seconds(3.0, TimerMode::Once)),
+            ));
+            shielded = true;
+        } else {
+            score.add(ScoreSource::Overheal, 10);
+        }
+        commands.entity(entity).despawn_recursive();
+        commands.spawn(AudioBundle {
+            source: assets.shoot.clone(),
+            settings: PlaybackSettings::DESPAWN.with_speed(0.8),
+        });
+    }
+}
+
+/// Gives every kill a small chance to drop a heart, better on a long streak or late in a run.
+fn drop_hearts(
+    mut commands: Commands,
+    mut damaged_events: EventReader<PlayerDamagedEvent>,
+    mut killed_events: EventReader<EnemyKilledEvent>,
+    mut streak: ResMut<KillStreak>,
+    stats: Res<RunStats>,
+    assets: Res<GameAssets>,
+) {
+    if !damaged_events.is_empty() {
+        damaged_events.clear();
+        streak.0 = 0;
+    }
+
+    let mut rng = rand::thread_rng();
+    for event in killed_events.read() {
+        streak.0 += 1;
+        let chance = 0.02
+            + (0.002 * streak.0 as f64).min(0.05)
+            + (0.0002 * stats.difficulty() as f64).min(0.03);
+        if rng.gen_bool(chance) {
+            let offset = Vec3::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0), 0.0);
+            commands.spawn(heart_bundle(event.position + offset, &assets));
+        }
+    }
+}
+
+fn expire_pickups(
+    mut commands: Commands,
+    mut pickup_query: Query<(Entity, &mut Pickup, &mut Visibility)>,
+    time: Res<Time>,
+) {
+    for (entity, mut pickup, mut visibility) in &mut pickup_query {
+        if pickup.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let remaining = pickup.lifetime.remaining_secs();
+        if remaining > BLINK_TIME {
+            continue;
+        }
+        // Blink faster as the pickup runs out of time
+        let rate = 4.0 + 8.0 * (1.0 - remaining / BLINK_TIME);
+        *visibility = if (remaining * rate).fract() < 0.5 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
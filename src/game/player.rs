@@ -1,14 +1,15 @@
 use std::collections::VecDeque;
 
 use bevy::prelude::*;
+use bevy::time::Stopwatch;
 
 use crate::{GameAssets, GameState};
 
 use super::{
-    enemy::EnemyKind,
-    health::Health,
+    health::{Health, MaxHealth},
     projectile::{Radius, Team},
-    spark::SparkCallbacks,
+    spark::{self, SparkCallbacks, SparkCharge, SparkKind},
+    status::StatusEffects,
     Game,
 };
 
@@ -16,23 +17,74 @@ use super::{
 pub struct Player {
     pub move_speed: f32,
     pub punch_distance: f32,
+    /// Spark orbs within this distance get pulled in.
+    pub magnet_radius: f32,
 }
 
 #[derive(Component)]
 struct Wand;
 
+/// A bar beside the wand that fills up as the next spark charges.
+#[derive(Component)]
+struct ChargeMeter;
+
+/// The spark being charged, `None` being a punch, and how long the use button has been held for.
+#[derive(Component, Default)]
+struct Charging(Option<(Option<SparkKind>, Stopwatch)>);
+
+/// How fast the player moved last frame, in units per second.
+#[derive(Component, Deref, DerefMut, Default)]
+pub struct PlayerVelocity(pub Vec2);
+
 #[derive(Component, Deref, DerefMut)]
-pub struct Sparks(pub VecDeque<EnemyKind>);
+pub struct Sparks(pub VecDeque<SparkKind>);
 
 #[derive(Component, Deref, DerefMut)]
 pub struct PunchCooldown(Timer);
 
+/// How long after a punch hostile projectiles can still be deflected.
+const PARRY_WINDOW: f32 = 0.15;
+
+/// How long after one parry window opens before a punch can open another.
+const PARRY_REARM: f32 = 0.4;
+
+/// Hostile projectiles can be deflected by a punch while `window` is running.
+#[derive(Component)]
+pub struct ParryWindow {
+    pub window: Timer,
+    /// Stops mashing punch from keeping the window open.
+    pub rearm: Timer,
+}
+
+impl ParryWindow {
+    fn new(window: f32, rearm: f32) -> Self {
+        let mut window = Timer::from_seconds(window, TimerMode::Once);
+        let mut rearm = Timer::from_seconds(rearm, TimerMode::Once);
+        window.tick(window.duration());
+        rearm.tick(rearm.duration());
+        Self { window, rearm }
+    }
+
+    /// Opens a new window, unless the last one was too recent.
+    pub fn open(&mut self) {
+        if self.rearm.finished() {
+            self.window.reset();
+            self.rearm.reset();
+        }
+    }
+}
+
 #[derive(Component, Deref, DerefMut)]
 struct HurtCooldown(Timer);
 
+/// Where the cursor is in world space.
+#[derive(Resource, Deref, Default)]
+pub struct CursorPosition(pub Vec2);
+
 #[derive(Bundle)]
 struct PlayerBundle {
     player: Player,
+    velocity: PlayerVelocity,
     game: Game,
     #[bundle()]
     sprite: SpriteBundle,
@@ -40,27 +92,74 @@ struct PlayerBundle {
     radius: Radius,
     sparks: Sparks,
     health: Health,
+    max_health: MaxHealth,
     punch_cooldown: PunchCooldown,
+    parry_window: ParryWindow,
     hurt_cooldown: HurtCooldown,
+    charging: Charging,
+    status_effects: StatusEffects,
 }
 
+/// Sent whenever the player uses a spark, `None` being a plain punch.
 #[derive(Event)]
-pub struct HurtPlayerEvent;
+pub struct SparkUsedEvent(pub Option<SparkKind>);
+
+#[derive(Event)]
+pub struct HurtPlayerEvent {
+    /// The enemy responsible, if it hit the player directly.
+    pub source: Option<Entity>,
+    pub cause: DamageCause,
+}
+
+/// Sent when a hit actually costs the player health, unlike hits swallowed by the hurt cooldown.
+#[derive(Event)]
+pub struct PlayerDamagedEvent {
+    /// The enemy responsible, if it hit the player directly.
+    pub source: Option<Entity>,
+    pub cause: DamageCause,
+}
+
+/// What kind of attack hurt the player.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageCause {
+    Melee,
+    Projectile,
+    Explosion,
+    Burn,
+}
+
+impl DamageCause {
+    pub const ALL: [Self; 4] = [Self::Melee, Self::Projectile, Self::Explosion, Self::Burn];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Melee => "Melee",
+            Self::Projectile => "Projectiles",
+            Self::Explosion => "Explosions",
+            Self::Burn => "Burns",
+        }
+    }
+}
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<HurtPlayerEvent>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<SparkUsedEvent>()
+            .init_resource::<CursorPosition>()
             .add_systems(OnEnter(GameState::Playing), spawn_player)
             .add_systems(
                 Update,
                 (
                     move_player,
-                    turn_player,
+                    track_cursor,
+                    turn_player.after(track_cursor),
                     handle_use,
                     handle_hurt_events,
                     show_wand,
+                    update_charge_meter,
                 )
                     .run_if(in_state(GameState::Playing)),
             );
@@ -73,7 +172,9 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>) {
             player: Player {
                 move_speed: 300.0,
                 punch_distance: 40.0,
+                magnet_radius: 120.0,
             },
+            velocity: PlayerVelocity::default(),
             game: Game,
             sprite: SpriteBundle {
                 texture: assets.player.clone(),
@@ -87,29 +188,50 @@ fn spawn_player(mut commands: Commands, assets: Res<GameAssets>) {
             radius: Radius(19.0),
             sparks: Sparks(VecDeque::new()),
             health: Health(3),
+            max_health: MaxHealth(3),
             punch_cooldown: PunchCooldown(Timer::from_seconds(0.5, TimerMode::Once)),
+            parry_window: ParryWindow::new(PARRY_WINDOW, PARRY_REARM),
             hurt_cooldown: HurtCooldown(Timer::from_seconds(0.5, TimerMode::Once)),
+            charging: Charging::default(),
+            status_effects: StatusEffects::default(),
         })
         .with_children(|parent| {
-            parent.spawn((
-                SpriteBundle {
-                    texture: assets.wand.clone(),
-                    sprite: Sprite {
-                        custom_size: Some(Vec2 { x: 16.0, y: 39.0 }),
+            parent
+                .spawn((
+                    SpriteBundle {
+                        texture: assets.wand.clone(),
+                        sprite: Sprite {
+                            custom_size: Some(Vec2 { x: 16.0, y: 39.0 }),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0.0, 29.0, 1.0),
+                        visibility: Visibility::Hidden,
                         ..Default::default()
                     },
-                    transform: Transform::from_xyz(0.0, 29.0, 1.0),
-                    visibility: Visibility::Hidden,
-                    ..Default::default()
-                },
-                Wand,
-            ));
+                    Wand,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(Vec2 { x: 4.0, y: 39.0 }),
+                                // Grow upwards from the bottom of the wand
+                                anchor: bevy::sprite::Anchor::BottomCenter,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(12.0, -19.5, 0.0)
+                                .with_scale(Vec3::new(1.0, 0.0, 1.0)),
+                            ..Default::default()
+                        },
+                        ChargeMeter,
+                    ));
+                });
         });
 }
 
 fn move_player(
     keyboard: Res<Input<KeyCode>>,
-    mut player_query: Query<(&mut Transform, &Player)>,
+    mut player_query: Query<(&mut Transform, &mut PlayerVelocity, &StatusEffects, &Player)>,
     time: Res<Time>,
 ) {
     let mut movement = Vec3::ZERO;
@@ -126,16 +248,17 @@ fn move_player(
         movement.x += 1.0;
     }
 
-    if movement != Vec3::ZERO {
-        let (mut transform, player) = player_query.single_mut();
-        transform.translation += movement.normalize() * player.move_speed * time.delta_seconds();
-    }
+    let (mut transform, mut velocity, effects, player) = player_query.single_mut();
+    **velocity =
+        movement.truncate().normalize_or_zero() * player.move_speed * effects.speed_multiplier()
+            + effects.knockback();
+    transform.translation += velocity.extend(0.0) * time.delta_seconds();
 }
 
-fn turn_player(
+fn track_cursor(
     window_query: Query<&Window>,
-    mut player_query: Query<&mut Transform, With<Player>>,
     camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut cursor: ResMut<CursorPosition>,
 ) {
     let (camera, camera_transform) = camera_query.single();
     let Some(cursor_pos) = window_query
@@ -146,24 +269,64 @@ fn turn_player(
     else {
         return;
     };
+    cursor.0 = cursor_pos;
+}
+
+fn turn_player(mut player_query: Query<&mut Transform, With<Player>>, cursor: Res<CursorPosition>) {
     let mut transform = player_query.single_mut();
-    let direction = (cursor_pos - transform.translation.truncate()).normalize();
+    let Some(direction) = (**cursor - transform.translation.truncate()).try_normalize() else {
+        return;
+    };
     transform.rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
 }
 
+/// Uses the next spark, straight away if it can't be charged, otherwise once the button is let go.
 fn handle_use(
     mut commands: Commands,
-    mut player_query: Query<(&mut Sparks, &mut PunchCooldown)>,
+    mut player_query: Query<(&mut Sparks, &mut PunchCooldown, &mut Charging)>,
     spark_callbacks: Res<SparkCallbacks>,
+    mut charge: ResMut<SparkCharge>,
+    mut used_writer: EventWriter<SparkUsedEvent>,
     mouse: Res<Input<MouseButton>>,
     time: Res<Time>,
 ) {
-    let (mut sparks, mut timer) = player_query.single_mut();
+    let (mut sparks, mut timer, mut charging) = player_query.single_mut();
     timer.tick(time.delta());
 
+    if let Some((_, held)) = &mut charging.0 {
+        held.tick(time.delta());
+    }
+
     if mouse.just_pressed(MouseButton::Left) {
-        let callback = spark_callbacks(sparks.pop_front());
-        commands.run_system(callback);
+        let next = sparks.front().copied();
+        let chargeable = spark::charge_time(next).is_some();
+        // Punches cost nothing, so they land straight away and again on release if charged
+        if next.is_none() || !chargeable {
+            sparks.pop_front();
+            **charge = 0.0;
+            commands.run_system(spark_callbacks(next));
+            used_writer.send(SparkUsedEvent(next));
+        }
+        if chargeable {
+            charging.0 = Some((next, Stopwatch::new()));
+        }
+    } else if mouse.just_released(MouseButton::Left) {
+        let Some((next, held)) = charging.0.take() else {
+            return;
+        };
+        if sparks.front().copied() != next {
+            return; // The spark was stolen or a new one picked up mid-charge, so the charge is lost
+        }
+        let Some(limits) = spark::charge_time(next) else {
+            return;
+        };
+        if next.is_none() && held.elapsed_secs() < limits.tap {
+            return; // A tapped punch already landed on press
+        }
+        sparks.pop_front();
+        **charge = limits.level(held.elapsed_secs());
+        commands.run_system(spark_callbacks(next));
+        used_writer.send(SparkUsedEvent(next));
     }
 }
 
@@ -171,6 +334,7 @@ fn handle_hurt_events(
     mut commands: Commands,
     mut hurt_events: EventReader<HurtPlayerEvent>,
     mut player_query: Query<(&mut Health, &mut HurtCooldown)>,
+    mut damaged_writer: EventWriter<PlayerDamagedEvent>,
     time: Res<Time>,
     mut next_state: ResMut<NextState<GameState>>,
     assets: Res<GameAssets>,
@@ -178,7 +342,15 @@ fn handle_hurt_events(
     let (mut health, mut cooldown) = player_query.single_mut();
 
     if cooldown.tick(time.delta()).finished() && !hurt_events.is_empty() {
+        // Only one hit gets through each cooldown, so it takes the blame
+        let first = hurt_events
+            .read()
+            .next()
+            .map(|event| (event.source, event.cause));
         hurt_events.clear();
+        if let Some((source, cause)) = first {
+            damaged_writer.send(PlayerDamagedEvent { source, cause });
+        }
         **health -= 1;
         cooldown.reset();
         commands.spawn(AudioBundle {
@@ -201,3 +373,24 @@ fn show_wand(mut wand_query: Query<&mut Visibility, With<Wand>>, mouse: Res<Inpu
         *visibility = Visibility::Hidden;
     }
 }
+
+fn update_charge_meter(
+    player_query: Query<(&Sparks, &Charging)>,
+    mut meter_query: Query<(&mut Transform, &mut Sprite), With<ChargeMeter>>,
+) {
+    let (sparks, charging) = player_query.single();
+    let (mut transform, mut sprite) = meter_query.single_mut();
+
+    let level = match &charging.0 {
+        Some((next, held)) if sparks.front().copied() == *next => {
+            spark::charge_time(*next).map_or(0.0, |limits| limits.level(held.elapsed_secs()))
+        }
+        _ => 0.0,
+    };
+    transform.scale.y = level;
+    sprite.color = if level >= 1.0 {
+        Color::WHITE
+    } else {
+        Color::rgb(1.0, 0.6 + 0.3 * level, 0.2)
+    };
+}
@@ -1,14 +1,88 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
 use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{GameAssets, GameState};
 
-use super::{health::Health, player::HurtPlayerEvent, Game};
+use super::{
+    health::Health,
+    player::{DamageCause, HurtPlayerEvent, Player},
+    stats::RunStats,
+    status::{HitFlash, StatusEffect, StatusEffects},
+    time_scale::TimeScale,
+    Game,
+};
 
 #[derive(Component)]
 pub struct Projectile;
 
-#[derive(Component, Deref, Clone, Copy)]
-pub struct Velocity(pub f32);
+/// Fired by the player, as opposed to parried or reflected back at enemies.
+#[derive(Component)]
+pub struct PlayerShot;
+
+/// How fast a projectile moves, in units per second.
+#[derive(Component, Deref, DerefMut, Clone, Copy)]
+pub struct Velocity(pub Vec2);
+
+impl Velocity {
+    /// Moves at `speed` in the direction `transform` is facing.
+    pub fn forward(transform: &Transform, speed: f32) -> Self {
+        Self(transform.local_y().truncate() * speed)
+    }
+}
+
+/// Optional ways for a projectile to deviate from a straight line.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Motion {
+    acceleration: f32,
+    angular_velocity: f32,
+    homing: Option<f32>,
+    wave: Option<Wave>,
+    age: f32,
+}
+
+#[derive(Clone, Copy)]
+struct Wave {
+    amplitude: f32,
+    frequency: f32,
+}
+
+impl Motion {
+    /// Speeds the projectile up (or slows it down) along its direction of travel.
+    pub fn accelerating(self, acceleration: f32) -> Self {
+        Self {
+            acceleration,
+            ..self
+        }
+    }
+
+    /// Constantly turns the projectile, in radians per second, for curving shots.
+    pub fn curving(self, angular_velocity: f32) -> Self {
+        Self {
+            angular_velocity,
+            ..self
+        }
+    }
+
+    /// Turns towards the nearest target on the other team by at most `turn_rate` radians per second.
+    pub fn homing(self, turn_rate: f32) -> Self {
+        Self {
+            homing: Some(turn_rate),
+            ..self
+        }
+    }
+
+    /// Weaves `amplitude` units from side to side, `frequency` times per second.
+    pub fn waving(self, amplitude: f32, frequency: f32) -> Self {
+        Self {
+            wave: Some(Wave {
+                amplitude,
+                frequency,
+            }),
+            ..self
+        }
+    }
+}
 
 #[derive(Component, PartialEq, Eq, Clone, Copy)]
 pub enum Team {
@@ -16,9 +90,61 @@ pub enum Team {
     Hostile,
 }
 
+impl Team {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Friendly => Self::Hostile,
+            Self::Hostile => Self::Friendly,
+        }
+    }
+}
+
 #[derive(Component, Deref)]
 pub struct Radius(pub f32);
 
+/// Blocks projectiles arriving within `arc` radians either side of the way the entity is facing.
+#[derive(Component)]
+pub struct Shield {
+    pub arc: f32,
+    /// Whether blocked projectiles bounce back instead of being absorbed.
+    pub reflect: bool,
+}
+
+impl Shield {
+    /// Whether a hit arriving from `direction` (relative to the target) is blocked.
+    fn covers(&self, target: &Transform, direction: Vec2) -> bool {
+        direction.angle_between(target.local_y().truncate()).abs() <= self.arc
+    }
+}
+
+/// Bursts into a fan of this many smaller projectiles when it hits something.
+#[derive(Component)]
+pub struct SplitOnImpact(pub usize);
+
+/// A status effect applied to whatever the projectile hits.
+#[derive(Component)]
+pub struct Inflicts(pub StatusEffect);
+
+/// The fading trail left behind by a hitscan beam.
+#[derive(Component)]
+pub struct Beam {
+    pub start: Vec2,
+    pub end: Vec2,
+    pub color: Color,
+    pub timer: Timer,
+}
+
+impl Beam {
+    pub fn new(start: Vec2, end: Vec2, color: Color) -> Self {
+        Self {
+            start,
+            end,
+            color,
+            timer: Timer::from_seconds(0.25, TimerMode::Once),
+        }
+    }
+}
+
 #[derive(Bundle)]
 pub struct ProjectileBundle {
     pub projectile: Projectile,
@@ -36,28 +162,121 @@ impl Plugin for ProjectilePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (move_projectile, check_collisions).run_if(in_state(GameState::Playing)),
+            (move_projectile, check_collisions, draw_shields, draw_beams)
+                .run_if(in_state(GameState::Playing)),
         );
     }
 }
 
-fn move_projectile(mut projectile_query: Query<(&Velocity, &mut Transform)>, time: Res<Time>) {
-    for (&velocity, mut transform) in &mut projectile_query {
-        let forward = transform.local_y();
-        transform.translation += forward * *velocity * time.delta_seconds();
+fn move_projectile(
+    mut projectile_query: Query<(
+        &mut Velocity,
+        &mut Transform,
+        Option<&mut Motion>,
+        Option<&Team>,
+    )>,
+    target_query: Query<(&Transform, &Team), (With<Health>, Without<Velocity>)>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    for (mut velocity, mut transform, motion, team) in &mut projectile_query {
+        let delta = team.map_or(time.delta_seconds(), |&team| {
+            time_scale.delta_seconds(&time, team)
+        });
+        let position = transform.translation.truncate();
+        let mut offset = Vec2::ZERO;
+
+        if let Some(mut motion) = motion {
+            motion.age += delta;
+            let direction = velocity.normalize_or_zero();
+            **velocity += direction * motion.acceleration * delta;
+            **velocity = Vec2::from_angle(motion.angular_velocity * delta).rotate(**velocity);
+
+            if let (Some(turn_rate), Some(team)) = (motion.homing, team) {
+                let nearest = target_query
+                    .iter()
+                    .filter(|(_, target_team)| *target_team != team)
+                    .map(|(target, _)| target.translation.truncate())
+                    .min_by(|a, b| {
+                        a.distance_squared(position)
+                            .total_cmp(&b.distance_squared(position))
+                    });
+                if let Some(target) = nearest {
+                    let angle = velocity.angle_between(target - position);
+                    let turn = angle.clamp(-turn_rate * delta, turn_rate * delta);
+                    **velocity = Vec2::from_angle(turn).rotate(**velocity);
+                }
+            }
+
+            if let Some(wave) = motion.wave {
+                let omega = TAU * wave.frequency;
+                offset =
+                    direction.perp() * wave.amplitude * omega * (omega * motion.age).cos() * delta;
+            }
+        }
+
+        transform.translation += (**velocity * delta + offset).extend(0.0);
+        if let Some(direction) = velocity.try_normalize() {
+            transform.rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
+        }
     }
 }
 
 fn check_collisions(
     mut commands: Commands,
-    projectile_query: Query<(&Transform, &Radius, &Team, Entity), With<Projectile>>,
-    mut target_query: Query<(&Transform, &Radius, &Team, &mut Health)>,
+    mut projectile_query: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &Radius,
+            &mut Team,
+            Entity,
+            Option<&Inflicts>,
+            Option<&SplitOnImpact>,
+            Has<PlayerShot>,
+        ),
+        With<Projectile>,
+    >,
+    mut target_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Radius,
+            &Team,
+            &mut Health,
+            Option<&mut StatusEffects>,
+            Option<&Shield>,
+            Has<Player>,
+        ),
+        Without<Projectile>,
+    >,
     mut hurt_event_writer: EventWriter<HurtPlayerEvent>,
+    mut stats: ResMut<RunStats>,
+    assets: Res<GameAssets>,
 ) {
-    for (projectile_transform, projectile_radius, projectile_team, projectile) in &projectile_query
+    for (
+        mut projectile_transform,
+        mut projectile_velocity,
+        projectile_radius,
+        mut projectile_team,
+        projectile,
+        inflicts,
+        split,
+        player_shot,
+    ) in &mut projectile_query
     {
-        for (target_transform, target_radius, target_team, mut target_health) in &mut target_query {
-            if projectile_team == target_team {
+        for (
+            target,
+            target_transform,
+            target_radius,
+            target_team,
+            mut target_health,
+            mut target_effects,
+            shield,
+            is_player,
+        ) in &mut target_query
+        {
+            if *projectile_team == *target_team {
                 continue; // Skip if both are on the same team
             }
             if projectile_transform
@@ -68,11 +287,130 @@ fn check_collisions(
                 continue; // Skip if too far apart
             }
 
+            let normal = (projectile_transform.translation - target_transform.translation)
+                .truncate()
+                .normalize_or_zero();
+            if let Some(shield) = shield.filter(|shield| shield.covers(target_transform, normal)) {
+                if shield.reflect {
+                    let along_normal = projectile_velocity.dot(normal);
+                    **projectile_velocity -= 2.0 * along_normal * normal;
+                    if let Some(direction) = projectile_velocity.try_normalize() {
+                        projectile_transform.rotation =
+                            Quat::from_rotation_arc_2d(Vec2::Y, direction);
+                    }
+                    projectile_transform.translation = target_transform.translation
+                        + normal.extend(0.0) * (projectile_radius.0 + target_radius.0);
+                    *projectile_team = *target_team;
+                } else {
+                    commands.entity(projectile).despawn_recursive();
+                }
+                break;
+            }
+
             commands.entity(projectile).despawn_recursive();
-            match target_team {
-                Team::Friendly => hurt_event_writer.send(HurtPlayerEvent),
-                Team::Hostile => **target_health = target_health.saturating_sub(1),
+            if let Some(&SplitOnImpact(count)) = split {
+                split_projectile(
+                    &mut commands,
+                    &projectile_transform,
+                    target_transform.translation,
+                    target_radius.0,
+                    *projectile_team,
+                    count,
+                    &assets,
+                );
             }
+            if let (Some(inflicts), Some(effects)) = (inflicts, target_effects.as_mut()) {
+                effects.apply(inflicts.0.clone());
+            }
+            if is_player {
+                hurt_event_writer.send(HurtPlayerEvent {
+                    source: None,
+                    cause: DamageCause::Projectile,
+                });
+            } else {
+                **target_health = target_health.saturating_sub(1);
+                if player_shot {
+                    stats.shots_hit += 1;
+                }
+                if let Some(effects) = target_effects.as_mut() {
+                    effects.hit(projectile_transform.local_y().truncate() * 300.0, 0.1);
+                }
+                commands.entity(target).try_insert(HitFlash::default());
+            }
+            break; // A projectile only ever hits one target
+        }
+    }
+}
+
+fn draw_shields(shield_query: Query<(&Transform, &Radius, &Shield)>, mut gizmos: Gizmos) {
+    for (transform, radius, shield) in &shield_query {
+        let forward = transform.local_y();
+        gizmos.arc_2d(
+            transform.translation.truncate(),
+            forward.x.atan2(forward.y),
+            (2.0 * shield.arc).min(TAU),
+            radius.0 + 6.0,
+            Color::rgb(0.6, 0.8, 1.0),
+        );
+    }
+}
+
+fn draw_beams(
+    mut commands: Commands,
+    mut beam_query: Query<(Entity, &mut Beam)>,
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut beam) in &mut beam_query {
+        if beam.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let fade = 1.0 - beam.timer.percent();
+        let side = (beam.end - beam.start).normalize_or_zero().perp();
+        // Thin out from a few parallel lines down to one as it fades
+        for offset in [-2.0, 0.0, 2.0] {
+            let offset = side * offset * fade;
+            gizmos.line_2d(
+                beam.start + offset,
+                beam.end + offset,
+                beam.color.with_a(fade),
+            );
         }
     }
 }
+
+/// Fans `count` projectiles out of the far side of whatever was hit.
+fn split_projectile(
+    commands: &mut Commands,
+    transform: &Transform,
+    target_position: Vec3,
+    target_radius: f32,
+    team: Team,
+    count: usize,
+    assets: &GameAssets,
+) {
+    let spread = FRAC_PI_2;
+    for i in 0..count {
+        let angle = spread * (i as f32 / (count - 1).max(1) as f32 - 0.5);
+        let rotation = transform.rotation * Quat::from_rotation_z(angle);
+        let position = target_position + rotation * Vec3::Y * (target_radius + 10.0);
+        let fan_transform = Transform::from_translation(position).with_rotation(rotation);
+        commands.spawn(ProjectileBundle {
+            projectile: Projectile,
+            game: Game,
+            sprite: SpriteBundle {
+                texture: assets.bullet.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2 { x: 15.0, y: 15.0 }),
+                    ..Default::default()
+                },
+                transform: fan_transform,
+                ..Default::default()
+            },
+            velocity: Velocity::forward(&fan_transform, 500.0),
+            team,
+            radius: Radius(7.5),
+        });
+    }
+}
@@ -0,0 +1,275 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::{
+    health::Health,
+    projectile::{Radius, Team},
+};
+
+/// Something a cast or overlap found, `distance` is measured from where the query started.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub distance: f32,
+}
+
+/// How far along a segment starting at `origin` it first touches a circle, if it does at all.
+/// `direction` should be normalised.
+pub fn segment_circle(
+    origin: Vec2,
+    direction: Vec2,
+    length: f32,
+    center: Vec2,
+    radius: f32,
+) -> Option<f32> {
+    let to_center = center - origin;
+    let along = to_center.dot(direction);
+    let closest = origin + direction * along.clamp(0.0, length);
+    if closest.distance_squared(center) > radius.powi(2) {
+        return None;
+    }
+
+    // Step back from the closest point to where the segment enters the circle
+    let gap_squared = to_center.length_squared() - along.powi(2);
+    let entry = along - (radius.powi(2) - gap_squared).max(0.0).sqrt();
+    Some(entry.clamp(0.0, length))
+}
+
+/// Whether a circle pokes into the cone `half_angle` radians either side of `direction`.
+pub fn cone_circle(
+    origin: Vec2,
+    direction: Vec2,
+    half_angle: f32,
+    range: f32,
+    center: Vec2,
+    radius: f32,
+) -> bool {
+    let to_center = center - origin;
+    let distance = to_center.length();
+    if distance <= radius {
+        return true;
+    }
+    if distance - radius > range {
+        return false;
+    }
+    // Widen the cone by however much of the view the circle takes up
+    let angular_radius = (radius / distance).asin();
+    direction.angle_between(to_center).abs() <= half_angle + angular_radius
+}
+
+pub fn circle_circle(a: Vec2, a_radius: f32, b: Vec2, b_radius: f32) -> bool {
+    a.distance_squared(b) <= (a_radius + b_radius).powi(2)
+}
+
+/// Casts and overlap tests against everything with a `Radius` that can be damaged.
+/// Every query takes a team to filter by, `None` finds everything.
+#[derive(SystemParam)]
+pub struct Colliders<'w, 's> {
+    query:
+        Query<'w, 's, (Entity, &'static Transform, &'static Radius, &'static Team), With<Health>>,
+}
+
+impl Colliders<'_, '_> {
+    /// Everything along an endless ray, nearest first.
+    pub fn ray_cast(&self, origin: Vec2, direction: Vec2, team: Option<Team>) -> Vec<Hit> {
+        self.segment_cast(origin, direction, f32::INFINITY, team)
+    }
+
+    /// Everything along a segment of `length`, nearest first.
+    pub fn segment_cast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        length: f32,
+        team: Option<Team>,
+    ) -> Vec<Hit> {
+        let direction = direction.normalize_or_zero();
+        self.collect(team, |center, radius| {
+            segment_circle(origin, direction, length, center, radius)
+        })
+    }
+
+    /// Everything within `range` and `half_angle` radians of `direction`, nearest first.
+    pub fn cone(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        half_angle: f32,
+        range: f32,
+        team: Option<Team>,
+    ) -> Vec<Hit> {
+        self.collect(team, |center, radius| {
+            cone_circle(origin, direction, half_angle, range, center, radius)
+                .then(|| center.distance(origin))
+        })
+    }
+
+    /// Everything touching a circle, nearest to its centre first.
+    pub fn circle(&self, center: Vec2, radius: f32, team: Option<Team>) -> Vec<Hit> {
+        self.collect(team, |other, other_radius| {
+            circle_circle(center, radius, other, other_radius).then(|| center.distance(other))
+        })
+    }
+
+    fn collect(&self, team: Option<Team>, test: impl Fn(Vec2, f32) -> Option<f32>) -> Vec<Hit> {
+        let mut hits: Vec<Hit> = self
+            .query
+            .iter()
+            .filter(|(_, _, _, &other_team)| !team.is_some_and(|team| team != other_team))
+            .filter_map(|(entity, transform, radius, _)| {
+                let position = transform.translation.truncate();
+                test(position, radius.0).map(|distance| Hit {
+                    entity,
+                    position,
+                    distance,
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use bevy::ecs::system::SystemState;
+
+    use super::*;
+
+    #[test]
+    fn segment_hits_circle_in_front() {
+        let hit = segment_circle(Vec2::ZERO, Vec2::Y, 100.0, Vec2::new(0.0, 50.0), 10.0);
+        assert_eq!(hit, Some(40.0));
+    }
+
+    #[test]
+    fn segment_grazes_circle_to_the_side() {
+        let hit = segment_circle(Vec2::ZERO, Vec2::Y, 100.0, Vec2::new(10.0, 50.0), 10.0);
+        assert_eq!(hit, Some(50.0));
+        assert_eq!(
+            segment_circle(Vec2::ZERO, Vec2::Y, 100.0, Vec2::new(11.0, 50.0), 10.0),
+            None
+        );
+    }
+
+    #[test]
+    fn segment_stops_at_its_length() {
+        assert_eq!(
+            segment_circle(Vec2::ZERO, Vec2::Y, 30.0, Vec2::new(0.0, 50.0), 10.0),
+            None
+        );
+        // The far end just reaches inside the circle
+        let hit = segment_circle(Vec2::ZERO, Vec2::Y, 45.0, Vec2::new(0.0, 50.0), 10.0);
+        assert_eq!(hit, Some(40.0));
+    }
+
+    #[test]
+    fn segment_misses_circle_behind() {
+        assert_eq!(
+            segment_circle(Vec2::ZERO, Vec2::Y, 100.0, Vec2::new(0.0, -50.0), 10.0),
+            None
+        );
+    }
+
+    #[test]
+    fn segment_starting_inside_circle_hits_immediately() {
+        let hit = segment_circle(Vec2::ZERO, Vec2::Y, 100.0, Vec2::new(0.0, -5.0), 10.0);
+        assert_eq!(hit, Some(0.0));
+    }
+
+    #[test]
+    fn cone_includes_circles_poking_over_its_edge() {
+        let direction = Vec2::Y;
+        assert!(cone_circle(
+            Vec2::ZERO,
+            direction,
+            FRAC_PI_4,
+            100.0,
+            Vec2::new(30.0, 40.0),
+            1.0
+        ));
+        assert!(!cone_circle(
+            Vec2::ZERO,
+            direction,
+            FRAC_PI_4,
+            100.0,
+            Vec2::new(50.0, 10.0),
+            1.0
+        ));
+        assert!(cone_circle(
+            Vec2::ZERO,
+            direction,
+            FRAC_PI_4,
+            100.0,
+            Vec2::new(50.0, 10.0),
+            40.0
+        ));
+    }
+
+    #[test]
+    fn cone_respects_range() {
+        let center = Vec2::new(0.0, 120.0);
+        assert!(!cone_circle(Vec2::ZERO, Vec2::Y, 0.1, 100.0, center, 10.0));
+        assert!(cone_circle(Vec2::ZERO, Vec2::Y, 0.1, 100.0, center, 25.0));
+    }
+
+    #[test]
+    fn circles_touching() {
+        assert!(circle_circle(Vec2::ZERO, 5.0, Vec2::new(10.0, 0.0), 5.0));
+        assert!(!circle_circle(Vec2::ZERO, 5.0, Vec2::new(10.1, 0.0), 5.0));
+    }
+
+    fn spawn(world: &mut World, position: Vec2, radius: f32, team: Team) -> Entity {
+        world
+            .spawn((
+                Transform::from_translation(position.extend(0.0)),
+                Radius(radius),
+                team,
+                Health(1),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn casts_filter_by_team_and_sort_by_distance() {
+        let mut world = World::new();
+        let far = spawn(&mut world, Vec2::new(0.0, 300.0), 10.0, Team::Hostile);
+        let near = spawn(&mut world, Vec2::new(0.0, 100.0), 10.0, Team::Hostile);
+        let friend = spawn(&mut world, Vec2::new(0.0, 200.0), 10.0, Team::Friendly);
+        spawn(&mut world, Vec2::new(100.0, 100.0), 10.0, Team::Hostile);
+        // Without health there's nothing to hit
+        world.spawn((
+            Transform::from_xyz(0.0, 150.0, 0.0),
+            Radius(10.0),
+            Team::Hostile,
+        ));
+
+        let mut state = SystemState::<Colliders>::new(&mut world);
+        let colliders = state.get(&world);
+
+        let entities = |hits: Vec<Hit>| hits.into_iter().map(|hit| hit.entity).collect::<Vec<_>>();
+        assert_eq!(
+            entities(colliders.ray_cast(Vec2::ZERO, Vec2::Y, Some(Team::Hostile))),
+            [near, far]
+        );
+        assert_eq!(
+            entities(colliders.ray_cast(Vec2::ZERO, Vec2::Y, None)),
+            [near, friend, far]
+        );
+        assert_eq!(
+            entities(colliders.segment_cast(Vec2::ZERO, Vec2::Y * 2.0, 250.0, None)),
+            [near, friend]
+        );
+        assert_eq!(
+            entities(colliders.cone(Vec2::ZERO, Vec2::Y, 0.1, 1000.0, Some(Team::Friendly))),
+            [friend]
+        );
+        assert_eq!(
+            entities(colliders.circle(Vec2::new(0.0, 240.0), 55.0, None)),
+            [friend, far]
+        );
+    }
+}
@@ -2,7 +2,25 @@ use bevy::prelude::*;
 
 use crate::{GameAssets, GameState};
 
-use super::{enemy::Enemy, health::Health};
+use super::{
+    enemy::{EnemyHitEvent, EnemyKilledEvent},
+    player::{PlayerDamagedEvent, SparkUsedEvent},
+    spark::ParryEvent,
+    stats::RunStats,
+    Game,
+};
+
+/// Seconds the combo lasts without another kill.
+const COMBO_WINDOW: f32 = 3.0;
+
+/// Kills this close together count towards the same multi-kill.
+const MULTI_KILL_WINDOW: f32 = 0.3;
+
+/// Kills this soon after using a spark are credited to it.
+const SPARK_WINDOW: f32 = 1.0;
+
+/// Seconds the player has to go unhurt for each no-damage bonus.
+const NO_DAMAGE_INTERVAL: f32 = 15.0;
 
 #[derive(Component)]
 struct ScoreDisplay;
@@ -10,49 +28,313 @@ struct ScoreDisplay;
 #[derive(Component)]
 struct HighScoreDisplay;
 
+#[derive(Component)]
+struct ComboDisplay;
+
+/// Shows how long is left before the combo drops.
+#[derive(Component)]
+struct ComboBar;
+
 pub struct ScorePlugin;
 
 #[derive(Resource, Default)]
 pub struct Score {
     pub score: usize,
     pub high_score: usize,
+    /// Points earned this run from each source, indexed by `ScoreSource`.
+    breakdown: [usize; ScoreSource::ALL.len()],
+}
+
+impl Score {
+    pub fn add(&mut self, source: ScoreSource, points: usize) {
+        self.score += points;
+        self.breakdown[source as usize] += points;
+        self.high_score = self.high_score.max(self.score);
+    }
+
+    /// How many points each source contributed this run, leaving out any that gave nothing.
+    pub fn breakdown(&self) -> impl Iterator<Item = (ScoreSource, usize)> + '_ {
+        ScoreSource::ALL
+            .into_iter()
+            .map(|source| (source, self.breakdown[source as usize]))
+            .filter(|&(_, points)| points > 0)
+    }
+}
+
+/// Everything the player can earn points for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ScoreSource {
+    /// A point for every point of damage dealt.
+    Hits,
+    /// Each kill's points, times the combo multiplier.
+    Kills,
+    /// Several kills at once.
+    MultiKills,
+    Parries,
+    /// Going a while without getting hurt.
+    NoDamage,
+    /// Killing several enemies with a single spark.
+    SparkEfficiency,
+    /// Killing enemies with affixes.
+    Elites,
+    /// Hearts picked up at full health and shield.
+    Overheal,
+}
+
+impl ScoreSource {
+    pub const ALL: [Self; 8] = [
+        Self::Hits,
+        Self::Kills,
+        Self::MultiKills,
+        Self::Parries,
+        Self::NoDamage,
+        Self::SparkEfficiency,
+        Self::Elites,
+        Self::Overheal,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Hits => "Hits",
+            Self::Kills => "Kills",
+            Self::MultiKills => "Multi-kills",
+            Self::Parries => "Parries",
+            Self::NoDamage => "No damage",
+            Self::SparkEfficiency => "Spark efficiency",
+            Self::Elites => "Elites",
+            Self::Overheal => "Overheal",
+        }
+    }
+}
+
+/// Kills landing within a moment of each other.
+#[derive(Resource)]
+struct MultiKill {
+    count: usize,
+    timer: Timer,
+}
+
+/// Kills made with the spark used most recently.
+#[derive(Resource)]
+struct SparkKills {
+    count: usize,
+    timer: Timer,
+}
+
+/// Time since the player was last hurt, paying out every `NO_DAMAGE_INTERVAL` seconds.
+#[derive(Resource)]
+struct NoDamage {
+    streak: usize,
+    timer: Timer,
+}
+
+/// A timer that starts out finished, so nothing is in progress until it's reset.
+fn finished_timer(seconds: f32, mode: TimerMode) -> Timer {
+    let mut timer = Timer::from_seconds(seconds, mode);
+    timer.tick(timer.duration());
+    timer
+}
+
+impl Default for MultiKill {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            timer: finished_timer(MULTI_KILL_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+impl Default for SparkKills {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            timer: finished_timer(SPARK_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+impl Default for NoDamage {
+    fn default() -> Self {
+        Self {
+            streak: 0,
+            timer: Timer::from_seconds(NO_DAMAGE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Kills in quick succession, which multiply the score they're worth.
+#[derive(Resource)]
+pub struct Combo {
+    pub count: usize,
+    timer: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            timer: Timer::from_seconds(COMBO_WINDOW, TimerMode::Once),
+        }
+    }
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> usize {
+        match self.count {
+            0..=4 => 1,
+            5..=9 => 2,
+            10..=19 => 3,
+            20..=34 => 4,
+            _ => 5,
+        }
+    }
 }
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Score>()
+            .init_resource::<Combo>()
+            .init_resource::<MultiKill>()
+            .init_resource::<SparkKills>()
+            .init_resource::<NoDamage>()
             .add_systems(OnExit(GameState::Splash), spawn_scoreboard)
-            .add_systems(OnEnter(GameState::Playing), (reset_score, show_scoreboard))
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_score, show_scoreboard, spawn_combo_display),
+            )
             .add_systems(OnExit(GameState::Playing), hide_scoreboard)
             .add_systems(
                 Update,
-                (update_score, update_scoreboard).run_if(in_state(GameState::Playing)),
+                (
+                    (decay_combo, track_spark_uses, score_kills).chain(),
+                    score_hits,
+                    score_parries,
+                    score_no_damage,
+                    update_scoreboard,
+                    update_combo_display,
+                )
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
 
-fn reset_score(mut score: ResMut<Score>) {
+fn reset_score(
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut multi_kill: ResMut<MultiKill>,
+    mut spark_kills: ResMut<SparkKills>,
+    mut no_damage: ResMut<NoDamage>,
+) {
     score.score = 0;
+    score.breakdown = Default::default();
+    *combo = Combo::default();
+    *multi_kill = MultiKill::default();
+    *spark_kills = SparkKills::default();
+    *no_damage = NoDamage::default();
 }
 
-fn update_score(
+/// Drops the combo once it runs out of time or the player gets hurt.
+fn decay_combo(
+    mut combo: ResMut<Combo>,
+    mut damaged_events: EventReader<PlayerDamagedEvent>,
+    time: Res<Time>,
+) {
+    let hurt = !damaged_events.is_empty();
+    damaged_events.clear();
+    if combo.timer.tick(time.delta()).finished() || hurt {
+        combo.count = 0;
+    }
+}
+
+fn track_spark_uses(
+    mut used_events: EventReader<SparkUsedEvent>,
+    mut spark_kills: ResMut<SparkKills>,
+    time: Res<Time>,
+) {
+    spark_kills.timer.tick(time.delta());
+    for SparkUsedEvent(kind) in used_events.read() {
+        // Punches have nothing to be efficient with
+        if kind.is_some() {
+            spark_kills.count = 0;
+            spark_kills.timer.reset();
+        }
+    }
+}
+
+fn score_kills(
+    mut killed_events: EventReader<EnemyKilledEvent>,
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut multi_kill: ResMut<MultiKill>,
+    mut spark_kills: ResMut<SparkKills>,
+    mut stats: ResMut<RunStats>,
+    time: Res<Time>,
+) {
+    if multi_kill.timer.tick(time.delta()).finished() {
+        multi_kill.count = 0;
+    }
+
+    for event in killed_events.read() {
+        combo.count += 1;
+        combo.timer.reset();
+        stats.max_combo = stats.max_combo.max(combo.count);
+        score.add(ScoreSource::Kills, event.kind.points() * combo.multiplier());
+
+        multi_kill.count += 1;
+        multi_kill.timer.reset();
+        if multi_kill.count >= 2 {
+            // Every extra kill in the burst is worth more than the last
+            score.add(ScoreSource::MultiKills, 5 * (multi_kill.count - 1));
+        }
+
+        if !spark_kills.timer.finished() {
+            spark_kills.count += 1;
+            if spark_kills.count >= 2 {
+                score.add(ScoreSource::SparkEfficiency, 3);
+            }
+        }
+    }
+}
+
+fn score_hits(
     mut commands: Commands,
+    mut hit_events: EventReader<EnemyHitEvent>,
     mut score: ResMut<Score>,
-    enemy_query: Query<Ref<Health>, (Changed<Health>, With<Enemy>)>,
     assets: Res<GameAssets>,
 ) {
-    let hurt_enemies = enemy_query.iter().filter(|x| !x.is_added()).count();
+    if hit_events.is_empty() {
+        return;
+    }
+    for event in hit_events.read() {
+        score.add(ScoreSource::Hits, event.damage);
+    }
+    commands.spawn(AudioBundle {
+        source: assets.hit_enemy.clone(),
+        ..Default::default()
+    });
+}
 
-    score.score += hurt_enemies;
-    if score.score > score.high_score {
-        score.high_score = score.score;
+fn score_parries(mut parry_events: EventReader<ParryEvent>, mut score: ResMut<Score>) {
+    for _ in parry_events.read() {
+        score.add(ScoreSource::Parries, 3);
     }
+}
 
-    if hurt_enemies != 0 {
-        commands.spawn(AudioBundle {
-            source: assets.hit_enemy.clone(),
-            ..Default::default()
-        });
+/// Pays out more the longer the player goes without being hurt.
+fn score_no_damage(
+    mut damaged_events: EventReader<PlayerDamagedEvent>,
+    mut no_damage: ResMut<NoDamage>,
+    mut score: ResMut<Score>,
+    time: Res<Time>,
+) {
+    if !damaged_events.is_empty() {
+        damaged_events.clear();
+        *no_damage = NoDamage::default();
+        return;
+    }
+    if no_damage.timer.tick(time.delta()).just_finished() {
+        no_damage.streak += 1;
+        score.add(ScoreSource::NoDamage, 10 * no_damage.streak);
     }
 }
 
@@ -134,3 +416,79 @@ fn update_scoreboard(
     let mut high_score_text = high_score_query.single_mut();
     high_score_text.sections[1].value = score.high_score.to_string();
 }
+
+fn spawn_combo_display(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexEnd,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Game,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                ComboDisplay,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(150.0),
+                        height: Val::Px(8.0),
+                        ..Default::default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::rgb(1.0, 0.85, 0.3).into(),
+                            ..Default::default()
+                        },
+                        ComboBar,
+                    ));
+                });
+        });
+}
+
+fn update_combo_display(
+    combo: Res<Combo>,
+    mut text_query: Query<&mut Text, With<ComboDisplay>>,
+    mut bar_query: Query<&mut Style, With<ComboBar>>,
+) {
+    let mut text = text_query.single_mut();
+    text.sections[0].value = if combo.count == 0 {
+        String::new()
+    } else {
+        format!("x{} Combo {}", combo.multiplier(), combo.count)
+    };
+
+    let mut bar = bar_query.single_mut();
+    let remaining = if combo.count == 0 {
+        0.0
+    } else {
+        1.0 - combo.timer.percent()
+    };
+    bar.width = Val::Percent(100.0 * remaining);
+}
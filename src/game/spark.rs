@@ -1,4 +1,4 @@
-use std::f32::consts::TAU;
+use std::f32::consts::{PI, TAU};
 
 use bevy::ecs::system::SystemId;
 use bevy::prelude::*;
@@ -6,25 +6,161 @@ use bevy::prelude::*;
 use crate::{GameAssets, GameState};
 
 use super::{
-    enemy::{Enemy, EnemyKind},
-    health::Health,
-    player::{Player, PunchCooldown, Sparks},
-    projectile::{Projectile, ProjectileBundle, Radius, Team, Velocity},
-    Game,
+    explosion::Bomb,
+    health::{Health, MaxHealth},
+    minion::MinionBundle,
+    pattern::BulletPattern,
+    player::{CursorPosition, ParryWindow, Player, PunchCooldown, Sparks},
+    projectile::{Beam, Motion, Projectile, Radius, Shield, Team, Velocity},
+    raycast::{segment_circle, Colliders},
+    stats::RunStats,
+    status::{HitFlash, StatusEffect, StatusEffects},
+    time_scale::TimeScale,
+    Game, ARENA_EXTENT,
 };
 
 #[derive(Component)]
 struct SparkDisplay;
 
+/// Every kind of spark the player can carry.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SparkKind {
+    Basic,
+    Ranged,
+    Boss,
+    Splitter,
+    Charger,
+    Shielded,
+    Healer,
+    Thief,
+    Bomber,
+    Beam,
+    Summon,
+    Charm,
+    Blink,
+    BulletTime,
+}
+
+impl SparkKind {
+    pub const ALL: [Self; 14] = [
+        Self::Basic,
+        Self::Ranged,
+        Self::Boss,
+        Self::Splitter,
+        Self::Charger,
+        Self::Shielded,
+        Self::Healer,
+        Self::Thief,
+        Self::Bomber,
+        Self::Beam,
+        Self::Summon,
+        Self::Charm,
+        Self::Blink,
+        Self::BulletTime,
+    ];
+
+    /// Sparks handed out as the bonus for killing an elite.
+    pub const RARE: [Self; 5] = [
+        Self::Beam,
+        Self::Summon,
+        Self::Charm,
+        Self::Blink,
+        Self::BulletTime,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Basic => "Basic",
+            Self::Ranged => "Ranged",
+            Self::Boss => "Boss",
+            Self::Splitter => "Splitter",
+            Self::Charger => "Charger",
+            Self::Shielded => "Shielded",
+            Self::Healer => "Healer",
+            Self::Thief => "Thief",
+            Self::Bomber => "Bomber",
+            Self::Beam => "Beam",
+            Self::Summon => "Summon",
+            Self::Charm => "Charm",
+            Self::Blink => "Blink",
+            Self::BulletTime => "Bullet time",
+        }
+    }
+}
+
+/// A temporary shield, removed along with the `Shield` once the timer runs out.
+#[derive(Component, Deref, DerefMut)]
+pub struct Barrier(pub Timer);
+
+/// How charged the spark being used is, from 0 for a tap up to 1 when fully charged.
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct SparkCharge(pub f32);
+
+impl SparkCharge {
+    /// Blends between a tapped and a fully charged value.
+    fn lerp(&self, tapped: f32, charged: f32) -> f32 {
+        tapped + (charged - tapped) * self.0
+    }
+
+    fn count(&self, tapped: usize, charged: usize) -> usize {
+        self.lerp(tapped as f32, charged as f32).round() as usize
+    }
+}
+
+/// How long a spark can be held down to charge it.
+#[derive(Clone, Copy)]
+pub struct ChargeTime {
+    /// Letting go any sooner than this counts as a tap.
+    pub tap: f32,
+    /// Holding for this long charges the spark fully.
+    pub full: f32,
+}
+
+impl ChargeTime {
+    pub fn level(&self, held: f32) -> f32 {
+        ((held - self.tap) / (self.full - self.tap)).clamp(0.0, 1.0)
+    }
+}
+
+/// How long each spark takes to charge, `None` for sparks that fire as soon as they're pressed.
+///
+/// Chargeable sparks fire when the button is released, apart from the punch which also lands on press.
+pub fn charge_time(kind: Option<SparkKind>) -> Option<ChargeTime> {
+    let (tap, full) = match kind {
+        None => (0.2, 0.8),
+        Some(
+            SparkKind::Basic
+            | SparkKind::Ranged
+            | SparkKind::Splitter
+            | SparkKind::Bomber
+            | SparkKind::Charm,
+        ) => (0.2, 1.0),
+        Some(SparkKind::Charger | SparkKind::Blink) => (0.2, 0.8),
+        Some(SparkKind::Beam) => (0.25, 1.2),
+        Some(SparkKind::Boss | SparkKind::Summon) => (0.3, 1.5),
+        Some(
+            SparkKind::Shielded | SparkKind::Healer | SparkKind::Thief | SparkKind::BulletTime,
+        ) => return None,
+    };
+    Some(ChargeTime { tap, full })
+}
+
+/// Sent whenever the player knocks a projectile back.
+#[derive(Event)]
+pub struct ParryEvent;
+
 pub struct SparkPlugin;
 
 impl Plugin for SparkPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<SparkCallbacks>()
+        app.add_event::<ParryEvent>()
+            .init_resource::<SparkCallbacks>()
+            .init_resource::<SparkCharge>()
             .add_systems(OnEnter(GameState::Playing), spawn_spark_display)
             .add_systems(
                 Update,
-                update_spark_display.run_if(in_state(GameState::Playing)),
+                (update_spark_display, handle_parry, expire_barriers)
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
@@ -93,10 +229,7 @@ fn update_spark_display(
                 });
             } else {
                 for spark in sparks.0.iter() {
-                    let sprite = match spark {
-                        EnemyKind::Basic => assets.basic_spark.clone(),
-                        EnemyKind::Ranged => assets.ranged_spark.clone(),
-                    };
+                    let (sprite, tint) = spark_icon(*spark, &assets);
                     parent.spawn(ImageBundle {
                         style: Style {
                             width: Val::Px(80.0),
@@ -105,6 +238,7 @@ fn update_spark_display(
                             ..Default::default()
                         },
                         image: UiImage::new(sprite),
+                        background_color: tint.into(),
                         ..Default::default()
                     });
                 }
@@ -112,71 +246,211 @@ fn update_spark_display(
         });
 }
 
+/// The image and tint representing a spark, in the queue and on the ground.
+pub fn spark_icon(kind: SparkKind, assets: &GameAssets) -> (Handle<Image>, Color) {
+    match kind {
+        SparkKind::Basic => (assets.basic_spark.clone(), Color::WHITE),
+        SparkKind::Ranged => (assets.ranged_spark.clone(), Color::WHITE),
+        SparkKind::Boss => (assets.ranged_spark.clone(), Color::rgb(1.0, 0.4, 0.3)),
+        SparkKind::Splitter => (assets.basic_spark.clone(), Color::rgb(0.6, 1.0, 0.6)),
+        SparkKind::Charger => (assets.basic_spark.clone(), Color::rgb(1.0, 0.7, 0.4)),
+        SparkKind::Shielded => (assets.ranged_spark.clone(), Color::rgb(0.7, 0.7, 0.9)),
+        SparkKind::Healer => (assets.ranged_spark.clone(), Color::rgb(1.0, 0.6, 0.8)),
+        SparkKind::Thief => (assets.basic_spark.clone(), Color::rgb(0.5, 0.5, 0.5)),
+        SparkKind::Bomber => (assets.basic_spark.clone(), Color::rgb(1.0, 0.5, 0.3)),
+        SparkKind::Beam => (assets.ranged_spark.clone(), Color::rgb(0.5, 0.9, 1.0)),
+        SparkKind::Summon => (assets.basic_spark.clone(), Color::rgb(0.7, 0.9, 1.0)),
+        SparkKind::Charm => (assets.ranged_spark.clone(), Color::rgb(1.0, 0.5, 0.8)),
+        SparkKind::Blink => (assets.basic_spark.clone(), Color::rgb(0.8, 0.8, 1.0)),
+        SparkKind::BulletTime => (assets.ranged_spark.clone(), Color::rgb(0.5, 0.7, 1.0)),
+    }
+}
+
 #[derive(Resource, Deref)]
-pub struct SparkCallbacks(Box<dyn Fn(Option<EnemyKind>) -> SystemId + Send + Sync>);
+pub struct SparkCallbacks(Box<dyn Fn(Option<SparkKind>) -> SystemId + Send + Sync>);
 
 impl FromWorld for SparkCallbacks {
     fn from_world(world: &mut World) -> Self {
         let punch = world.register_system(handle_punch);
         let basic = world.register_system(handle_basic);
         let ranged = world.register_system(handle_ranged);
+        let boss = world.register_system(handle_boss);
+        let splitter = world.register_system(handle_splitter);
+        let charger = world.register_system(handle_dash);
+        let shielded = world.register_system(handle_shield);
+        let healer = world.register_system(handle_heal);
+        let thief = world.register_system(handle_duplicate);
+        let bomber = world.register_system(handle_bomb);
+        let beam = world.register_system(handle_beam);
+        let summon = world.register_system(handle_summon);
+        let charm = world.register_system(handle_charm);
+        let blink = world.register_system(handle_blink);
+        let bullet_time = world.register_system(handle_bullet_time);
 
         Self(Box::new(move |kind| match kind {
             None => punch,
-            Some(EnemyKind::Basic) => basic,
-            Some(EnemyKind::Ranged) => ranged,
+            Some(SparkKind::Basic) => basic,
+            Some(SparkKind::Ranged) => ranged,
+            Some(SparkKind::Boss) => boss,
+            Some(SparkKind::Splitter) => splitter,
+            Some(SparkKind::Charger) => charger,
+            Some(SparkKind::Shielded) => shielded,
+            Some(SparkKind::Healer) => healer,
+            Some(SparkKind::Thief) => thief,
+            Some(SparkKind::Bomber) => bomber,
+            Some(SparkKind::Beam) => beam,
+            Some(SparkKind::Summon) => summon,
+            Some(SparkKind::Charm) => charm,
+            Some(SparkKind::Blink) => blink,
+            Some(SparkKind::BulletTime) => bullet_time,
         }))
     }
 }
 
 fn handle_punch(
-    mut player_query: Query<(&Transform, &mut PunchCooldown, &Player)>,
-    mut enemy_query: Query<(&Transform, &Radius, &mut Health), With<Enemy>>,
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut PunchCooldown, &mut ParryWindow, &Player)>,
+    mut enemy_query: Query<(&mut Health, &mut StatusEffects), Without<Player>>,
+    colliders: Colliders,
+    charge: Res<SparkCharge>,
+    mut stats: ResMut<RunStats>,
 ) {
-    let (player, mut timer, player_settings) = player_query.single_mut();
-    let cast_dist = player_settings.punch_distance;
+    let (player, mut timer, mut parry_window, player_settings) = player_query.single_mut();
+    let cast_dist = charge.lerp(
+        player_settings.punch_distance,
+        player_settings.punch_distance * 2.5,
+    );
 
     if !timer.finished() {
         return;
     }
 
-    for (enemy, radius, mut health) in &mut enemy_query {
-        let player_to_enemy = enemy.translation - player.translation;
-        let closest_point = player_to_enemy.dot(player.local_y());
-        if ((0.0..=cast_dist).contains(&closest_point)
-            && (player.local_y() * closest_point).distance_squared(player_to_enemy)
-                <= radius.powi(2))
-            || (player.local_y() * cast_dist).distance_squared(player_to_enemy) <= radius.powi(2)
-            || player_to_enemy.length_squared() <= radius.powi(2)
+    parry_window.open();
+
+    let origin = player.translation.truncate();
+    let facing = player.local_y().truncate();
+    let mut landed = false;
+    for hit in colliders.segment_cast(origin, facing, cast_dist, Some(Team::Hostile)) {
+        let Ok((mut health, mut effects)) = enemy_query.get_mut(hit.entity) else {
+            continue;
+        };
+        **health = health.saturating_sub(1);
+        let direction = (hit.position - origin).try_normalize();
+        effects.hit(
+            direction.unwrap_or(facing) * charge.lerp(1200.0, 2000.0),
+            0.3,
+        );
+        commands.entity(hit.entity).try_insert(HitFlash::default());
+        landed = true;
+        timer.reset();
+    }
+    if landed {
+        stats.punches_landed += 1;
+    }
+}
+
+/// Launches the player forwards, hitting every enemy along the way.
+fn handle_dash(
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut StatusEffects), With<Player>>,
+    mut enemy_query: Query<(&mut Health, &mut StatusEffects), Without<Player>>,
+    colliders: Colliders,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let (player, mut player_effects) = player_query.single_mut();
+    let distance = charge.lerp(250.0, 450.0);
+    let duration = 0.25;
+    let direction = player.local_y().truncate();
+
+    // A knockback covers half of `impulse * duration` as it fades out
+    player_effects.apply(StatusEffect::knockback(
+        direction * 2.0 * distance / duration,
+        duration,
+    ));
+
+    let origin = player.translation.truncate();
+    for hit in colliders.segment_cast(origin, direction, distance, Some(Team::Hostile)) {
+        let Ok((mut health, mut effects)) = enemy_query.get_mut(hit.entity) else {
+            continue;
+        };
+        **health = health.saturating_sub(1);
+        effects.hit(direction * 600.0, 0.3);
+        commands.entity(hit.entity).try_insert(HitFlash::default());
+    }
+
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        ..Default::default()
+    });
+}
+
+/// Deflects hostile projectiles that run into a fresh punch back towards the cursor.
+fn handle_parry(
+    mut commands: Commands,
+    mut player_query: Query<(&Transform, &mut PunchCooldown, &mut ParryWindow, &Player)>,
+    mut projectile_query: Query<
+        (
+            &mut Transform,
+            &mut Sprite,
+            &Radius,
+            &mut Team,
+            &mut Velocity,
+        ),
+        (With<Projectile>, Without<Player>),
+    >,
+    cursor: Res<CursorPosition>,
+    mut parry_writer: EventWriter<ParryEvent>,
+    assets: Res<GameAssets>,
+    time: Res<Time>,
+) {
+    let (player, mut timer, mut parry_window, player_settings) = player_query.single_mut();
+    parry_window.rearm.tick(time.delta());
+    if parry_window.window.tick(time.delta()).finished() {
+        return;
+    }
+
+    for (mut transform, mut sprite, radius, mut team, mut velocity) in &mut projectile_query {
+        if *team != Team::Hostile
+            || segment_circle(
+                player.translation.truncate(),
+                player.local_y().truncate(),
+                player_settings.punch_distance,
+                transform.translation.truncate(),
+                radius.0,
+            )
+            .is_none()
         {
-            **health -= 1;
-            timer.reset();
+            continue;
         }
+
+        let direction = (**cursor - transform.translation.truncate())
+            .try_normalize()
+            .unwrap_or(player.local_y().truncate());
+        transform.rotation = Quat::from_rotation_arc_2d(Vec2::Y, direction);
+        *team = Team::Friendly;
+        **velocity = direction * velocity.length() * 4.0;
+        sprite.color = Color::rgb(1.0, 0.9, 0.4);
+        parry_writer.send(ParryEvent);
+        timer.reset();
+
+        commands.spawn(AudioBundle {
+            source: assets.hit_enemy.clone(),
+            settings: PlaybackSettings::DESPAWN.with_speed(1.5),
+        });
     }
 }
 
 fn handle_basic(
     mut commands: Commands,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    charge: Res<SparkCharge>,
     assets: Res<GameAssets>,
 ) {
-    let transform = *player_query.single();
-    commands.spawn(ProjectileBundle {
-        projectile: Projectile,
-        game: Game,
-        sprite: SpriteBundle {
-            texture: assets.bullet.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2 { x: 19.0, y: 19.0 }),
-                ..Default::default()
-            },
-            transform,
-            ..Default::default()
-        },
-        velocity: Velocity(600.0),
-        team: Team::Friendly,
-        radius: Radius(9.5),
-    });
+    let (player, transform) = player_query.single();
+    BulletPattern::spread(charge.count(1, 5), TAU / 12.0, 600.0)
+        .inflicts(StatusEffect::burn(2.0))
+        .emit(&mut commands, player, transform.rotation, Team::Friendly);
     commands.spawn(AudioBundle {
         source: assets.shoot.clone(),
         ..Default::default()
@@ -184,68 +458,286 @@ fn handle_basic(
 }
 
 fn handle_ranged(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let (player, transform) = player_query.single();
+    BulletPattern::spread(
+        charge.count(3, 7),
+        charge.lerp(TAU / 32.0, TAU / 12.0),
+        600.0,
+    )
+    .inflicts(StatusEffect::slow(0.5, 2.0))
+    .emit(&mut commands, player, transform.rotation, Team::Friendly);
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        ..Default::default()
+    });
+}
+
+fn handle_boss(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let (player, transform) = player_query.single();
+    BulletPattern::ring(8, 700.0)
+        .bursts(charge.count(3, 6), 0.08)
+        .spiral(TAU / 24.0)
+        .bullet(25.0, Color::rgb(1.0, 0.5, 0.3))
+        .inflicts(StatusEffect::burn(3.0))
+        .emit(&mut commands, player, transform.rotation, Team::Friendly);
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        ..Default::default()
+    });
+}
+
+fn handle_splitter(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let (player, transform) = player_query.single();
+    BulletPattern::single(500.0)
+        .bullet(25.0, Color::rgb(0.6, 1.0, 0.6))
+        .splits(charge.count(5, 9))
+        .emit(&mut commands, player, transform.rotation, Team::Friendly);
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        ..Default::default()
+    });
+}
+
+/// Instantly hits everything in a long line, snapping onto an enemy close to where the player aims.
+fn handle_beam(
     mut commands: Commands,
     player_query: Query<&Transform, With<Player>>,
+    mut enemy_query: Query<(&mut Health, &mut StatusEffects), Without<Player>>,
+    colliders: Colliders,
+    charge: Res<SparkCharge>,
     assets: Res<GameAssets>,
 ) {
-    let mut transform = *player_query.single();
-    let angle = TAU / 32.0;
+    let player = player_query.single();
+    let start = player.translation.truncate();
+    let length = 1200.0;
+    let direction = colliders
+        .cone(
+            start,
+            player.local_y().truncate(),
+            0.15,
+            length,
+            Some(Team::Hostile),
+        )
+        .first()
+        .and_then(|hit| (hit.position - start).try_normalize())
+        .unwrap_or(player.local_y().truncate());
 
-    commands.spawn(ProjectileBundle {
-        projectile: Projectile,
-        game: Game,
-        sprite: SpriteBundle {
-            texture: assets.bullet.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2 { x: 19.0, y: 19.0 }),
-                ..Default::default()
-            },
-            transform,
-            ..Default::default()
-        },
-        velocity: Velocity(600.0),
-        team: Team::Friendly,
-        radius: Radius(9.5),
+    for hit in colliders.ray_cast(start, direction, Some(Team::Hostile)) {
+        let Ok((mut health, mut effects)) = enemy_query.get_mut(hit.entity) else {
+            continue;
+        };
+        **health = health.saturating_sub(charge.count(2, 4));
+        effects.hit(direction * 400.0, 0.2);
+        commands.entity(hit.entity).try_insert(HitFlash::default());
+    }
+
+    commands.spawn((
+        Beam::new(start, start + direction * length, Color::rgb(0.5, 0.9, 1.0)),
+        Game,
+    ));
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        settings: PlaybackSettings::DESPAWN.with_speed(0.7),
     });
+}
 
-    transform.rotate_local_z(-angle / 2.0);
-    commands.spawn(ProjectileBundle {
-        projectile: Projectile,
-        game: Game,
-        sprite: SpriteBundle {
-            texture: assets.bullet.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2 { x: 19.0, y: 19.0 }),
-                ..Default::default()
-            },
-            transform,
-            ..Default::default()
+/// Raises a few ghosts that hunt down the nearest enemies.
+fn handle_summon(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let player = player_query.single();
+    let count = charge.count(3, 6);
+    for i in 0..count {
+        let angle = TAU * i as f32 / count as f32;
+        let position = player.translation + Quat::from_rotation_z(angle) * Vec3::Y * 40.0;
+        commands.spawn(MinionBundle::ghost(position, &assets));
+    }
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        settings: PlaybackSettings::DESPAWN.with_speed(0.5),
+    });
+}
+
+/// Fires a bolt that turns whatever it hits against its friends for a while.
+fn handle_charm(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let (player, transform) = player_query.single();
+    // Starting slow gives the bolts time to turn towards a target before they pick up speed
+    BulletPattern::spread(charge.count(1, 3), TAU / 12.0, 250.0)
+        .bullet(22.0, Color::rgb(1.0, 0.5, 0.8))
+        .inflicts(StatusEffect::charm(5.0))
+        .motion(Motion::default().homing(2.0).accelerating(500.0))
+        .emit(&mut commands, player, transform.rotation, Team::Friendly);
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        settings: PlaybackSettings::DESPAWN.with_speed(1.3),
+    });
+}
+
+/// Teleports the player towards the cursor, jolting every enemy around where they land.
+fn handle_blink(
+    mut commands: Commands,
+    mut params: ParamSet<(Query<(&mut Transform, &Radius), With<Player>>, Colliders)>,
+    mut enemy_query: Query<(&mut Health, &mut StatusEffects), Without<Player>>,
+    cursor: Res<CursorPosition>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let (start, destination) = {
+        let mut player_query = params.p0();
+        let (mut transform, radius) = player_query.single_mut();
+        let start = transform.translation.truncate();
+        let bound = ARENA_EXTENT - radius.0;
+        let destination = (start + (**cursor - start).clamp_length_max(charge.lerp(250.0, 400.0)))
+            .clamp(Vec2::splat(-bound), Vec2::splat(bound));
+        transform.translation = destination.extend(transform.translation.z);
+        (start, destination)
+    };
+
+    for hit in params
+        .p1()
+        .circle(destination, charge.lerp(60.0, 100.0), Some(Team::Hostile))
+    {
+        let Ok((mut health, mut effects)) = enemy_query.get_mut(hit.entity) else {
+            continue;
+        };
+        **health = health.saturating_sub(1);
+        let direction = (hit.position - destination).normalize_or_zero();
+        effects.hit(direction * 900.0, 0.25);
+        commands.entity(hit.entity).try_insert(HitFlash::default());
+    }
+
+    commands.spawn((
+        Beam::new(start, destination, Color::rgba(0.8, 0.8, 1.0, 0.6)),
+        Game,
+    ));
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        settings: PlaybackSettings::DESPAWN.with_speed(1.8),
+    });
+}
+
+/// Slows every enemy, and everything they fire, to a crawl for a few seconds.
+fn handle_bullet_time(
+    mut commands: Commands,
+    mut time_scale: ResMut<TimeScale>,
+    assets: Res<GameAssets>,
+) {
+    time_scale.slow(Team::Hostile, 0.3, 4.0);
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        settings: PlaybackSettings::DESPAWN.with_speed(0.5),
+    });
+}
+
+fn handle_shield(
+    mut commands: Commands,
+    player_query: Query<Entity, With<Player>>,
+    assets: Res<GameAssets>,
+) {
+    commands.entity(player_query.single()).insert((
+        Shield {
+            arc: PI,
+            reflect: false,
         },
-        velocity: Velocity(600.0),
-        team: Team::Friendly,
-        radius: Radius(9.5),
+        Barrier(Timer::from_seconds(4.0, TimerMode::Once)),
+    ));
+    commands.spawn(AudioBundle {
+        source: assets.shoot.clone(),
+        ..Default::default()
     });
+}
 
-    transform.rotate_local_z(angle);
-    commands.spawn(ProjectileBundle {
-        projectile: Projectile,
-        game: Game,
-        sprite: SpriteBundle {
+fn handle_heal(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Health, &MaxHealth), With<Player>>,
+) {
+    let (player, mut health, max_health) = player_query.single_mut();
+    if **health < **max_health {
+        **health += 1;
+    }
+    commands.entity(player).try_insert(HitFlash::default());
+}
+
+/// Copies the next spark in the queue.
+fn handle_duplicate(mut player_query: Query<&mut Sparks>) {
+    let mut sparks = player_query.single_mut();
+    if let Some(&next) = sparks.front() {
+        sparks.push_front(next);
+    }
+}
+
+/// Lobs a bomb at the cursor.
+fn handle_bomb(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    cursor: Res<CursorPosition>,
+    charge: Res<SparkCharge>,
+    assets: Res<GameAssets>,
+) {
+    let transform = *player_query.single();
+    let speed = 400.0;
+    let distance = transform
+        .translation
+        .truncate()
+        .distance(**cursor)
+        .clamp(80.0, 400.0);
+
+    commands.spawn((
+        SpriteBundle {
             texture: assets.bullet.clone(),
             sprite: Sprite {
-                custom_size: Some(Vec2 { x: 19.0, y: 19.0 }),
+                color: Color::rgb(0.3, 0.2, 0.2),
+                custom_size: Some(Vec2 { x: 22.0, y: 22.0 }),
                 ..Default::default()
             },
             transform,
             ..Default::default()
         },
-        velocity: Velocity(600.0),
-        team: Team::Friendly,
-        radius: Radius(9.5),
-    });
-
+        Velocity::forward(&transform, speed),
+        Bomb {
+            fuse: Timer::from_seconds(distance / speed, TimerMode::Once),
+            radius: charge.lerp(110.0, 180.0),
+            damage: charge.count(3, 5),
+        },
+        Game,
+    ));
     commands.spawn(AudioBundle {
         source: assets.shoot.clone(),
         ..Default::default()
     });
 }
+
+fn expire_barriers(
+    mut commands: Commands,
+    mut barrier_query: Query<(Entity, &mut Barrier)>,
+    time: Res<Time>,
+) {
+    for (entity, mut barrier) in &mut barrier_query {
+        if barrier.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<(Shield, Barrier)>();
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_pkv::PkvStore;
+
+use crate::GameState;
+
+use super::{
+    enemy::{Enemy, EnemyHitEvent, EnemyKilledEvent, EnemyKind},
+    player::{DamageCause, PlayerDamagedEvent, SparkUsedEvent},
+    score::Score,
+    spark::{ParryEvent, SparkKind},
+};
+
+/// Statistics about the current run.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    /// Seconds since the run started.
+    pub time_survived: f32,
+    pub kills: HashMap<EnemyKind, usize>,
+    pub sparks_collected: HashMap<SparkKind, usize>,
+    pub sparks_used: HashMap<SparkKind, usize>,
+    /// Total damage dealt to enemies.
+    pub damage_dealt: usize,
+    pub punches_landed: usize,
+    pub parries: usize,
+    /// Hearts lost to each kind of attack.
+    pub damage_taken: HashMap<DamageCause, usize>,
+    /// Bullets fired by the player, not counting parried or reflected ones.
+    pub shots_fired: usize,
+    pub shots_hit: usize,
+    /// The most enemies alive at once.
+    pub peak_enemies: usize,
+    pub max_combo: usize,
+}
+
+impl RunStats {
+    pub fn total_kills(&self) -> usize {
+        self.kills.values().sum()
+    }
+
+    /// How far into the run the player is, for ramping up spawns and bosses.
+    /// Counts damage dealt rather than score, so combos and bonuses don't speed it up.
+    pub fn difficulty(&self) -> usize {
+        self.damage_dealt
+    }
+
+    /// The share of fired bullets that hit something, `None` if nothing was fired.
+    pub fn accuracy(&self) -> Option<f32> {
+        (self.shots_fired > 0).then(|| (self.shots_hit as f32 / self.shots_fired as f32).min(1.0))
+    }
+
+    /// Kills of each enemy kind that was killed at least once.
+    pub fn kills_by_kind(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        EnemyKind::ALL.into_iter().filter_map(|kind| {
+            let kills = self.kills.get(&kind).copied().unwrap_or_default();
+            (kills > 0).then(|| (kind.name(), kills))
+        })
+    }
+
+    /// How many of each spark were collected and used, leaving out any never seen.
+    pub fn sparks_by_kind(&self) -> impl Iterator<Item = (&'static str, usize, usize)> + '_ {
+        SparkKind::ALL.into_iter().filter_map(|kind| {
+            let collected = self
+                .sparks_collected
+                .get(&kind)
+                .copied()
+                .unwrap_or_default();
+            let used = self.sparks_used.get(&kind).copied().unwrap_or_default();
+            (collected > 0 || used > 0).then(|| (kind.name(), collected, used))
+        })
+    }
+
+    /// Hearts lost to each kind of attack that landed at least once.
+    pub fn damage_by_cause(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        DamageCause::ALL.into_iter().filter_map(|cause| {
+            let damage = self.damage_taken.get(&cause).copied().unwrap_or_default();
+            (damage > 0).then(|| (cause.label(), damage))
+        })
+    }
+}
+
+/// A stat the player tries to beat from one run to the next.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Record {
+    Score,
+    TimeSurvived,
+    Kills,
+    MaxCombo,
+    PunchesLanded,
+    Parries,
+}
+
+impl Record {
+    const ALL: [Self; 6] = [
+        Self::Score,
+        Self::TimeSurvived,
+        Self::Kills,
+        Self::MaxCombo,
+        Self::PunchesLanded,
+        Self::Parries,
+    ];
+
+    /// Where the best ever value is saved.
+    fn key(self) -> &'static str {
+        match self {
+            Self::Score => "best_score",
+            Self::TimeSurvived => "best_time_survived",
+            Self::Kills => "best_kills",
+            Self::MaxCombo => "best_max_combo",
+            Self::PunchesLanded => "best_punches_landed",
+            Self::Parries => "best_parries",
+        }
+    }
+
+    fn value(self, stats: &RunStats, score: &Score) -> f32 {
+        match self {
+            Self::Score => score.score as f32,
+            Self::TimeSurvived => stats.time_survived,
+            Self::Kills => stats.total_kills() as f32,
+            Self::MaxCombo => stats.max_combo as f32,
+            Self::PunchesLanded => stats.punches_landed as f32,
+            Self::Parries => stats.parries as f32,
+        }
+    }
+}
+
+/// Personal records the last run beat.
+#[derive(Resource, Default)]
+pub struct NewBests(Vec<Record>);
+
+impl NewBests {
+    pub fn contains(&self, record: Record) -> bool {
+        self.0.contains(&record)
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .init_resource::<NewBests>()
+            .add_systems(Startup, load_high_score)
+            .add_systems(OnEnter(GameState::Playing), reset_stats)
+            .add_systems(OnExit(GameState::Playing), save_bests)
+            .add_systems(
+                Update,
+                (
+                    count_time,
+                    count_kills,
+                    count_damage_dealt,
+                    count_spark_uses,
+                    count_parries,
+                    count_damage,
+                    count_enemies,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn count_time(mut stats: ResMut<RunStats>, time: Res<Time>) {
+    stats.time_survived += time.delta_seconds();
+}
+
+fn count_kills(mut killed_events: EventReader<EnemyKilledEvent>, mut stats: ResMut<RunStats>) {
+    for event in killed_events.read() {
+        *stats.kills.entry(event.kind).or_default() += 1;
+    }
+}
+
+fn count_damage_dealt(mut hit_events: EventReader<EnemyHitEvent>, mut stats: ResMut<RunStats>) {
+    for event in hit_events.read() {
+        stats.damage_dealt += event.damage;
+    }
+}
+
+fn count_spark_uses(mut used_events: EventReader<SparkUsedEvent>, mut stats: ResMut<RunStats>) {
+    for &SparkUsedEvent(kind) in used_events.read() {
+        if let Some(kind) = kind {
+            *stats.sparks_used.entry(kind).or_default() += 1;
+        }
+    }
+}
+
+fn count_parries(mut parry_events: EventReader<ParryEvent>, mut stats: ResMut<RunStats>) {
+    stats.parries += parry_events.read().count();
+}
+
+fn count_damage(mut damaged_events: EventReader<PlayerDamagedEvent>, mut stats: ResMut<RunStats>) {
+    for event in damaged_events.read() {
+        *stats.damage_taken.entry(event.cause).or_default() += 1;
+    }
+}
+
+fn count_enemies(enemy_query: Query<(), With<Enemy>>, mut stats: ResMut<RunStats>) {
+    stats.peak_enemies = stats.peak_enemies.max(enemy_query.iter().count());
+}
+
+/// Starts the high score off from the saved record, so the two always agree.
+fn load_high_score(mut score: ResMut<Score>, pkv: Res<PkvStore>) {
+    if let Ok(best) = pkv.get::<f32>(Record::Score.key()) {
+        score.high_score = best as usize;
+    }
+}
+
+/// Compares the run against the saved records, keeping any that were beaten.
+/// The first run to set a record has nothing to beat, so it isn't flagged as new.
+fn save_bests(
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    mut pkv: ResMut<PkvStore>,
+    mut new_bests: ResMut<NewBests>,
+) {
+    new_bests.0.clear();
+    for record in Record::ALL {
+        let value = record.value(&stats, &score);
+        match pkv.get::<f32>(record.key()) {
+            Ok(best) if value <= best => continue,
+            Ok(_) => new_bests.0.push(record),
+            Err(_) => {}
+        }
+        if let Err(err) = pkv.set(record.key(), &value) {
+            warn!("Couldn't save {}: {err}", record.key());
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use bevy::ecs::query::Has;
+use bevy::ecs::system::{Command, RunSystemOnce};
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    health::Health,
+    player::{DamageCause, HurtPlayerEvent, Player},
+    projectile::Team,
+    time_scale::TimeScale,
+};
+
+#[derive(Clone, Copy)]
+pub enum StatusKind {
+    /// Scales movement speed by the given factor.
+    Slow(f32),
+    /// Deals one damage every tick.
+    Burn,
+    /// Prevents moving and attacking.
+    Stun,
+    /// Pushes the entity along the given vector (in units per second), fading out over time.
+    Knockback(Vec2),
+    /// Fights for the player's team.
+    Charm,
+}
+
+#[derive(Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    duration: Timer,
+    tick: Timer,
+}
+
+impl StatusEffect {
+    fn new(kind: StatusKind, seconds: f32, tick_seconds: f32) -> Self {
+        Self {
+            kind,
+            duration: Timer::from_seconds(seconds, TimerMode::Once),
+            tick: Timer::from_seconds(tick_seconds, TimerMode::Repeating),
+        }
+    }
+
+    pub fn slow(factor: f32, seconds: f32) -> Self {
+        Self::new(StatusKind::Slow(factor), seconds, seconds)
+    }
+
+    pub fn burn(seconds: f32) -> Self {
+        Self::new(StatusKind::Burn, seconds, 1.0)
+    }
+
+    pub fn stun(seconds: f32) -> Self {
+        Self::new(StatusKind::Stun, seconds, seconds)
+    }
+
+    pub fn knockback(impulse: Vec2, seconds: f32) -> Self {
+        Self::new(StatusKind::Knockback(impulse), seconds, seconds)
+    }
+
+    pub fn charm(seconds: f32) -> Self {
+        Self::new(StatusKind::Charm, seconds, seconds)
+    }
+
+    fn remaining(&self) -> f32 {
+        self.duration.percent_left()
+    }
+}
+
+/// The colour a sprite is tinted when no effects are applied.
+#[derive(Component, Deref, Clone, Copy)]
+pub struct Tint(pub Color);
+
+/// Briefly brightens an entity's sprite after it takes a hit.
+#[derive(Component)]
+pub struct HitFlash(Timer);
+
+impl Default for HitFlash {
+    fn default() -> Self {
+        Self(Timer::from_seconds(0.15, TimerMode::Once))
+    }
+}
+
+/// After a hit stuns something, further hits can't stun it again for this long.
+const HIT_STUN_COOLDOWN: f32 = 1.0;
+
+/// Every timed effect currently applied to an entity.
+#[derive(Component)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+    /// Stops steady fire from keeping an enemy stunned forever.
+    hit_stun_cooldown: Timer,
+}
+
+impl Default for StatusEffects {
+    fn default() -> Self {
+        let mut hit_stun_cooldown = Timer::from_seconds(HIT_STUN_COOLDOWN, TimerMode::Once);
+        hit_stun_cooldown.tick(hit_stun_cooldown.duration());
+        Self {
+            effects: Vec::new(),
+            hit_stun_cooldown,
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        self.effects.push(effect);
+    }
+
+    /// Knocks the entity back along `impulse` and stuns it for a moment, unless a hit stunned it recently.
+    pub fn hit(&mut self, impulse: Vec2, stun_seconds: f32) {
+        self.apply(StatusEffect::knockback(impulse, 0.3));
+        if self.hit_stun_cooldown.finished() {
+            self.apply(StatusEffect::stun(stun_seconds));
+            self.hit_stun_cooldown.reset();
+        }
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| matches!(effect.kind, StatusKind::Stun))
+    }
+
+    pub fn is_charmed(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| matches!(effect.kind, StatusKind::Charm))
+    }
+
+    /// How much to scale voluntary movement by.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.is_stunned() {
+            return 0.0;
+        }
+        self.effects
+            .iter()
+            .filter_map(|effect| match effect.kind {
+                StatusKind::Slow(factor) => Some(factor),
+                _ => None,
+            })
+            .product()
+    }
+
+    /// The combined velocity of every knockback, in units per second.
+    pub fn knockback(&self) -> Vec2 {
+        self.effects
+            .iter()
+            .filter_map(|effect| match effect.kind {
+                StatusKind::Knockback(impulse) => Some(impulse * effect.remaining()),
+                _ => None,
+            })
+            .sum()
+    }
+
+    fn tint(&self, base: Color) -> Color {
+        self.effects.iter().fold(base, |tint, effect| {
+            let [red, green, blue, _] = match effect.kind {
+                StatusKind::Slow(_) => [0.5, 0.7, 1.0, 1.0],
+                StatusKind::Burn => [1.0, 0.6, 0.3, 1.0],
+                StatusKind::Stun => [1.0, 1.0, 0.4, 1.0],
+                StatusKind::Knockback(_) => [1.0, 1.0, 1.0, 1.0],
+                StatusKind::Charm => [1.0, 0.5, 0.8, 1.0],
+            };
+            Color::rgba(tint.r() * red, tint.g() * green, tint.b() * blue, tint.a())
+        })
+    }
+}
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                tick_status_effects,
+                tick_hit_flashes.before(tint_status_effects),
+                tint_status_effects,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn tick_status_effects(
+    mut commands: Commands,
+    mut status_query: Query<(Entity, &mut StatusEffects, Option<&Team>)>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    for (entity, mut effects, team) in &mut status_query {
+        // Effects run on their target's clock, so slowing time slows them too
+        let delta = team.map_or(time.delta(), |&team| time_scale.delta(&time, team));
+        effects.hit_stun_cooldown.tick(delta);
+        for effect in &mut effects.effects {
+            effect.duration.tick(delta);
+            for _ in 0..effect.tick.tick(delta).times_finished_this_tick() {
+                commands.add(StatusTickCommand {
+                    entity,
+                    kind: effect.kind,
+                });
+            }
+        }
+        effects.effects.retain(|effect| !effect.duration.finished());
+    }
+}
+
+fn tick_hit_flashes(
+    mut commands: Commands,
+    mut flash_query: Query<(Entity, &mut HitFlash)>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash) in &mut flash_query {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
+
+pub(super) fn tint_status_effects(
+    mut status_query: Query<(
+        &mut Sprite,
+        &StatusEffects,
+        Option<&Tint>,
+        Option<&HitFlash>,
+    )>,
+) {
+    for (mut sprite, effects, base, flash) in &mut status_query {
+        sprite.color = effects.tint(base.map_or(Color::WHITE, |base| **base));
+        if let Some(flash) = flash {
+            let brightness = 1.0 + 3.0 * flash.0.percent_left();
+            sprite.color = Color::rgba(
+                sprite.color.r() * brightness,
+                sprite.color.g() * brightness,
+                sprite.color.b() * brightness,
+                sprite.color.a(),
+            );
+        }
+    }
+}
+
+struct StatusTickCommand {
+    entity: Entity,
+    kind: StatusKind,
+}
+
+impl Command for StatusTickCommand {
+    fn apply(self, world: &mut World) {
+        match self.kind {
+            StatusKind::Burn => world.run_system_once_with(self.entity, handle_burn_tick),
+            StatusKind::Slow(_)
+            | StatusKind::Stun
+            | StatusKind::Knockback(_)
+            | StatusKind::Charm => {}
+        }
+    }
+}
+
+fn handle_burn_tick(
+    In(entity): In<Entity>,
+    mut health_query: Query<(&mut Health, Has<Player>)>,
+    mut hurt_event_writer: EventWriter<HurtPlayerEvent>,
+) {
+    let Ok((mut health, is_player)) = health_query.get_mut(entity) else {
+        return; // Entity died before command was executed
+    };
+
+    if is_player {
+        hurt_event_writer.send(HurtPlayerEvent {
+            source: None,
+            cause: DamageCause::Burn,
+        });
+    } else {
+        **health = health.saturating_sub(1);
+    }
+}
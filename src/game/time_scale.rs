@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{projectile::Team, Game};
+
+/// How fast time passes for each team, so one side can be slowed without touching the other.
+#[derive(Resource, Default)]
+pub struct TimeScale {
+    friendly: Slowdown,
+    hostile: Slowdown,
+}
+
+/// A temporary change to how fast a team's time runs, measured in real time.
+struct Slowdown {
+    factor: f32,
+    timer: Timer,
+}
+
+impl Default for Slowdown {
+    fn default() -> Self {
+        let mut timer = Timer::default();
+        timer.tick(timer.duration());
+        Self { factor: 1.0, timer }
+    }
+}
+
+impl TimeScale {
+    pub fn of(&self, team: Team) -> f32 {
+        let slowdown = match team {
+            Team::Friendly => &self.friendly,
+            Team::Hostile => &self.hostile,
+        };
+        if slowdown.timer.finished() {
+            1.0
+        } else {
+            slowdown.factor
+        }
+    }
+
+    /// How long the last frame lasted for `team`.
+    pub fn delta(&self, time: &Time, team: Team) -> Duration {
+        time.delta().mul_f32(self.of(team))
+    }
+
+    pub fn delta_seconds(&self, time: &Time, team: Team) -> f32 {
+        time.delta_seconds() * self.of(team)
+    }
+
+    /// Runs `team`'s time at `factor` speed for the next `seconds`.
+    pub fn slow(&mut self, team: Team, factor: f32, seconds: f32) {
+        let slowdown = match team {
+            Team::Friendly => &mut self.friendly,
+            Team::Hostile => &mut self.hostile,
+        };
+        *slowdown = Slowdown {
+            factor,
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        };
+    }
+}
+
+/// Washes the screen blue while hostile time is slowed.
+#[derive(Component)]
+struct SlowOverlay;
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeScale>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_time_scale, spawn_slow_overlay),
+            )
+            .add_systems(
+                Update,
+                (tick_time_scale, update_slow_overlay).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_time_scale(mut time_scale: ResMut<TimeScale>) {
+    *time_scale = TimeScale::default();
+}
+
+fn tick_time_scale(mut time_scale: ResMut<TimeScale>, time: Res<Time>) {
+    time_scale.friendly.timer.tick(time.delta());
+    time_scale.hostile.timer.tick(time.delta());
+}
+
+fn spawn_slow_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..Default::default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(-1),
+            ..Default::default()
+        },
+        SlowOverlay,
+        Game,
+    ));
+}
+
+fn update_slow_overlay(
+    mut overlay_query: Query<&mut BackgroundColor, With<SlowOverlay>>,
+    time_scale: Res<TimeScale>,
+) {
+    let slowed = 1.0 - time_scale.of(Team::Hostile);
+    for mut background in &mut overlay_query {
+        background.0 = Color::rgba(0.3, 0.5, 1.0, 0.15 * slowed);
+    }
+}
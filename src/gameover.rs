@@ -2,11 +2,54 @@ use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy_nine_slice_ui::NineSliceUiTexture;
 
-use crate::{game::score::Score, utils, GameAssets, GameState};
+use crate::{
+    game::{
+        score::Score,
+        stats::{NewBests, Record, RunStats},
+    },
+    utils, GameAssets, GameState,
+};
+
+/// Colour for stats that set a new personal record.
+const NEW_BEST: Color = Color::rgb(1.0, 0.85, 0.3);
 
 #[derive(Component)]
 struct GameOver;
 
+/// Switches the summary to the page with the same index.
+#[derive(Component)]
+struct SummaryTab(usize);
+
+#[derive(Component)]
+struct SummaryPage(usize);
+
+/// One page of the run summary, a title and its lines.
+struct Page {
+    title: &'static str,
+    lines: Vec<Line>,
+}
+
+struct Line {
+    text: String,
+    new_best: bool,
+}
+
+impl Line {
+    fn new(text: String) -> Self {
+        Self {
+            text,
+            new_best: false,
+        }
+    }
+
+    fn record(text: String, record: Record, new_bests: &NewBests) -> Self {
+        Self {
+            text,
+            new_best: new_bests.contains(record),
+        }
+    }
+}
+
 #[derive(Component)]
 enum GameOverButton {
     Retry,
@@ -18,12 +61,101 @@ pub struct GameOverPlugin;
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::GameOver), setup_menu)
-            .add_systems(Update, menu_action.run_if(in_state(GameState::GameOver)))
+            .add_systems(
+                Update,
+                (menu_action, switch_tabs).run_if(in_state(GameState::GameOver)),
+            )
             .add_systems(OnExit(GameState::GameOver), utils::despawn_with::<GameOver>);
     }
 }
 
-fn setup_menu(mut commands: Commands, assets: Res<GameAssets>, score: Res<Score>) {
+/// Lays out the run's stats, split into pages so they fit on screen.
+fn summary_pages(stats: &RunStats, score: &Score, new_bests: &NewBests) -> Vec<Page> {
+    let mut overview = vec![
+        Line::record(
+            format!("Time survived: {:.0}s", stats.time_survived),
+            Record::TimeSurvived,
+            new_bests,
+        ),
+        Line::record(
+            format!("Kills: {}", stats.total_kills()),
+            Record::Kills,
+            new_bests,
+        ),
+        Line::record(
+            format!("Max combo: {}", stats.max_combo),
+            Record::MaxCombo,
+            new_bests,
+        ),
+        Line::new(format!("Most enemies at once: {}", stats.peak_enemies)),
+    ];
+    overview.extend(
+        score
+            .breakdown()
+            .map(|(source, points)| Line::new(format!("{}: {}", source.label(), points))),
+    );
+
+    let mut combat = vec![
+        Line::record(
+            format!("Punches landed: {}", stats.punches_landed),
+            Record::PunchesLanded,
+            new_bests,
+        ),
+        Line::record(
+            format!("Parries: {}", stats.parries),
+            Record::Parries,
+            new_bests,
+        ),
+        Line::new(match stats.accuracy() {
+            Some(accuracy) => format!("Accuracy: {:.0}%", 100.0 * accuracy),
+            None => "Accuracy: -".to_string(),
+        }),
+    ];
+    combat.extend(
+        stats
+            .kills_by_kind()
+            .map(|(name, kills)| Line::new(format!("{name} kills: {kills}"))),
+    );
+
+    let sparks = stats
+        .sparks_by_kind()
+        .map(|(name, collected, used)| {
+            Line::new(format!("{name}: {collected} collected, {used} used"))
+        })
+        .collect();
+
+    let damage = stats
+        .damage_by_cause()
+        .map(|(cause, hearts)| Line::new(format!("{cause}: {hearts}")))
+        .collect();
+
+    vec![
+        Page {
+            title: "Overview",
+            lines: overview,
+        },
+        Page {
+            title: "Combat",
+            lines: combat,
+        },
+        Page {
+            title: "Sparks",
+            lines: sparks,
+        },
+        Page {
+            title: "Damage",
+            lines: damage,
+        },
+    ]
+}
+
+fn setup_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    new_bests: Res<NewBests>,
+) {
     let button_style = Style {
         width: Val::Px(250.0),
         height: Val::Px(65.0),
@@ -55,13 +187,18 @@ fn setup_menu(mut commands: Commands, assets: Res<GameAssets>, score: Res<Score>
             GameOver,
         ))
         .with_children(|parent| {
+            let score_color = if new_bests.contains(Record::Score) {
+                NEW_BEST
+            } else {
+                Color::WHITE
+            };
             parent.spawn(TextBundle::from_sections(vec![
                 TextSection {
                     value: "Score: ".to_string(),
                     style: TextStyle {
                         font: assets.font.clone(),
                         font_size: 60.0,
-                        color: Color::WHITE,
+                        color: score_color,
                     },
                 },
                 TextSection {
@@ -69,13 +206,90 @@ fn setup_menu(mut commands: Commands, assets: Res<GameAssets>, score: Res<Score>
                     style: TextStyle {
                         font: assets.font.clone(),
                         font_size: 60.0,
-                        color: Color::WHITE,
+                        color: score_color,
                     },
                 },
             ]));
+
+            let pages = summary_pages(&stats, &score, &new_bests);
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                for (i, page) in pages.iter().enumerate() {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(160.0),
+                                    height: Val::Px(50.0),
+                                    margin: UiRect::all(Val::Px(5.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            NineSliceUiTexture::from_image(assets.button_ninepatch.clone()),
+                            SummaryTab(i),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                page.title,
+                                TextStyle {
+                                    font: assets.font.clone(),
+                                    font_size: 28.0,
+                                    color: Color::BLACK,
+                                },
+                            ));
+                        });
+                }
+            });
+            for (i, page) in pages.into_iter().enumerate() {
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                display: if i == 0 { Display::Flex } else { Display::None },
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                min_height: Val::Px(300.0),
+                                margin: UiRect::vertical(Val::Px(10.0)),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        SummaryPage(i),
+                    ))
+                    .with_children(|parent| {
+                        if page.lines.is_empty() {
+                            parent.spawn(TextBundle::from_section(
+                                "Nothing yet",
+                                TextStyle {
+                                    font: assets.font.clone(),
+                                    font_size: 24.0,
+                                    color: Color::rgb(0.6, 0.6, 0.6),
+                                },
+                            ));
+                        }
+                        for line in page.lines {
+                            let (text, color) = if line.new_best {
+                                (format!("{} - New best!", line.text), NEW_BEST)
+                            } else {
+                                (line.text, Color::rgb(0.85, 0.85, 0.85))
+                            };
+                            parent.spawn(TextBundle::from_section(
+                                text,
+                                TextStyle {
+                                    font: assets.font.clone(),
+                                    font_size: 24.0,
+                                    color,
+                                },
+                            ));
+                        }
+                    });
+            }
+
             parent.spawn(ImageBundle {
                 style: Style {
-                    width: Val::Percent(25.0),
+                    width: Val::Percent(12.0),
                     height: Val::Auto,
                     ..Default::default()
                 },
@@ -133,3 +347,21 @@ fn menu_action(
         }
     }
 }
+
+fn switch_tabs(
+    tab_query: Query<(&Interaction, &SummaryTab), (Changed<Interaction>, With<Button>)>,
+    mut page_query: Query<(&mut Style, &SummaryPage)>,
+) {
+    for (interaction, tab) in &tab_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        for (mut style, page) in &mut page_query {
+            style.display = if page.0 == tab.0 {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+    }
+}
@@ -3,6 +3,7 @@ use bevy::{asset::AssetMetaCheck, window::WindowResolution};
 
 use bevy_asset_loader::prelude::*;
 use bevy_nine_slice_ui::NineSliceUiPlugin;
+use bevy_pkv::PkvStore;
 
 mod game;
 mod gameover;
@@ -82,6 +83,7 @@ fn main() {
             ..Default::default()
         }))
         .add_plugins(NineSliceUiPlugin::default())
+        .insert_resource(PkvStore::new("jam_game", "jam_game"))
         .add_state::<GameState>()
         .init_collection::<GameAssets>()
         .add_plugins((